@@ -11,6 +11,8 @@ contract LeaderBoardVerifierContract is ERC721 {
         uint256 score;
         bool win;
         uint256 endFrame;
+        uint256 day;
+        uint256 timeScore;
     }
 
     address public alignedServiceManager = 0x58F280BeBE9B34c9939C3C39e0890C81f163B623;
@@ -25,6 +27,9 @@ contract LeaderBoardVerifierContract is ERC721 {
 
     mapping(uint256 => GameScore) public leaderboard;
 
+    // tokenIds of the daily challenge runs, by UTC day number
+    mapping(uint256 => uint256[]) public dailyRuns;
+
     constructor() ERC721("Space Aligners", "SA") {}
 
     function verifyBatchInclusion(
@@ -87,19 +92,31 @@ contract LeaderBoardVerifierContract is ERC721 {
 
         uint256 tokenId = uint256(fullHash);
         _mint(msg.sender, tokenId);
-        (uint256 score, bool win, uint256 endFrame) =
-            abi.decode(pubInput, (uint256, bool, uint256));
+        _submitScore(tokenId, pubInput);
+
+        return tokenId;
+    }
+
+    // Decoded apart from verifyBatchInclusion to keep its stack small
+    function _submitScore(uint256 tokenId, bytes memory pubInput) internal {
+        (uint256 score, bool win, uint256 endFrame, , , uint256 day, uint256 timeScore) =
+            abi.decode(pubInput, (uint256, bool, uint256, bytes, bytes32, uint256, uint256));
         GameScore memory gameScore = GameScore(
             block.timestamp,
             score,
             win,
-            endFrame
+            endFrame,
+            day,
+            timeScore
         );
 
+        // Daily runs only rank on the day of the challenge, a late claim still mints
+        if (day != 0 && day == block.timestamp / 1 days) {
+            dailyRuns[day].push(tokenId);
+        }
+
         leaderboard[tokenId] = gameScore;
         emit SubmitScore(gameScore);
-
-        return tokenId;
     }
 
     function tokenURI(
@@ -1,9 +1,25 @@
+#![allow(clippy::manual_is_multiple_of)] // `is_multiple_of` is not available on the SP1 toolchain
+
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub mod level;
+use level::Level;
+pub mod generator;
+pub mod snapshot;
+pub mod input;
+pub mod replay;
+pub mod profile;
+pub mod public_values;
+use input::{InputSource, ReplayInput};
+
+pub const WIDTH: i32 = 60;
+pub const HEIGHT: i32 = 22;
+pub const MAX_FRAME: usize = 1023;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Vec2 {
-    x: i32,
-    y: i32,
+    pub x: i32,
+    pub y: i32,
 }
 
 impl Vec2 {
@@ -12,19 +28,70 @@ impl Vec2 {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlienKind {
+    Grunt,
+    Soldier,
+    Elite,
+}
+
+impl AlienKind {
+    pub fn points(&self) -> u8 {
+        match self {
+            AlienKind::Grunt => 5,
+            AlienKind::Soldier => 10,
+            AlienKind::Elite => 20,
+        }
+    }
+
+    // Elites shoot from any row and twice as often
+    fn must_shot(&self, position: Vec2, frame: usize) -> bool {
+        match self {
+            AlienKind::Grunt | AlienKind::Soldier => {
+                frame % 66 == 0 && position.x % 6 == 0 && position.y % 4 == 0
+            }
+            AlienKind::Elite => frame % 30 == 0 && position.x % 6 == 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Alien {
+    pub position: Vec2,
+    pub kind: AlienKind,
+}
+
+impl Alien {
+    pub fn new(position: Vec2, kind: AlienKind) -> Alien {
+        Alien { position, kind }
+    }
+}
+
+// What happened during a frame, reported by GameState::step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEvent {
+    AlienDestroyed(Alien),
+    PlayerHit { lives: usize },
+    ShotFired { position: Vec2, by_alien: bool },
+    FormationDescended,
+    GameOver { win: bool },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GameState {
     pub dimension: Vec2,
     pub spaceship: Vec2,
     pub spaceship_shots: Vec<Vec2>,
     pub last_shot_frame: usize,
-    pub aliens: Vec<Vec2>,
+    pub aliens: Vec<Alien>,
     pub aliens_shots: Vec<Vec2>,
+    pub bunkers: Vec<Vec2>,
     pub aliens_movement: (i32, bool), //dir, just_down
     pub last_aliens_movement: usize,
     pub last_aliens_shots: usize,
     pub lives: usize,
     pub score: u8,
-    pub user_input: Vec<(u16, u8)>
+    pub user_input: Vec<(u16, u8)>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -33,25 +100,58 @@ pub struct GameIO {
     pub win: bool, // If the ship survives
     pub end_frame: u16, // The final frame of the game(max 1023)
     pub inputs: String, // All user keyboard inputs
+    #[serde(default)]
+    pub seed: Option<String>, // Hex seed of a generated level, None for the classic one
+    #[serde(default)]
+    pub day: Option<u32>, // UTC day of a daily challenge run
+    pub time_score: u16, // Speedrun score, frames left when all the aliens are defeated
+}
+
+// GameIO JSON as any version wrote it, older ones have no seed, day or time score.
+// Only for JSON, binary formats like bincode need the exact fields of GameIO
+#[derive(Deserialize)]
+pub struct JsonGameIO {
+    score: u8,
+    win: bool,
+    end_frame: u16,
+    inputs: String,
+    #[serde(default)]
+    seed: Option<String>,
+    #[serde(default)]
+    day: Option<u32>,
+    #[serde(default)]
+    time_score: Option<u16>,
+}
+
+impl From<JsonGameIO> for GameIO {
+    fn from(stored: JsonGameIO) -> GameIO {
+        GameIO {
+            score: stored.score,
+            win: stored.win,
+            end_frame: stored.end_frame,
+            inputs: stored.inputs,
+            seed: stored.seed,
+            day: stored.day,
+            // The claimed result implies it, so runs from before time scores still verify
+            time_score: stored.time_score.unwrap_or_else(|| time_score(stored.win, stored.end_frame)),
+        }
+    }
 }
 
 impl GameState {
     pub fn new(dimension: Vec2) -> GameState {
-        let mut aliens = Vec::new();
-        for y in 3..7 {
-            for x in 25..dimension.x - 25 {
-                if x % 2 != 0 {
-                    aliens.push(Vec2::xy(x, y));
-                }
-            }
-        }
+        GameState::from_level(dimension, &Level::classic(dimension))
+    }
+
+    pub fn from_level(dimension: Vec2, level: &Level) -> GameState {
         GameState {
             dimension,
-            spaceship: Vec2::xy(dimension.x / 2, dimension.y - 2),
+            spaceship: level.spaceship,
             spaceship_shots: Vec::new(),
             last_shot_frame: 0,
-            aliens,
+            aliens: level.aliens.clone(),
             aliens_shots: Vec::new(),
+            bunkers: level.bunkers.clone(),
             aliens_movement: (1, false),
             last_aliens_movement: 0,
             last_aliens_shots: 0,
@@ -69,45 +169,64 @@ impl GameState {
         }
     }
 
-    pub fn spaceship_shot(&mut self, shot_frame: usize) {
+    pub fn spaceship_shot(&mut self, shot_frame: usize) -> Option<GameEvent> {
         if self.last_shot_frame + 15 < shot_frame {
             self.spaceship_shots.push(self.spaceship);
             self.last_shot_frame = shot_frame;
+            return Some(GameEvent::ShotFired { position: self.spaceship, by_alien: false });
         }
+        None
+    }
+
+    // Runs a whole frame after the user input: the ship shot and the update
+    pub fn step(&mut self, frame: usize) -> Vec<GameEvent> {
+        profile::start("spaceship_shot");
+        let mut events: Vec<GameEvent> = self.spaceship_shot(frame).into_iter().collect();
+        profile::end("spaceship_shot");
+        events.extend(self.update(frame));
+        events
     }
 
-    pub fn update(&mut self, frame: usize) {
+    pub fn update(&mut self, frame: usize) -> Vec<GameEvent> {
+        profile::start("collision");
+        let mut events = Vec::new();
         let mut partial_score = 0;
         let aliens = &mut self.aliens;
+        let bunkers = &mut self.bunkers;
         self.spaceship_shots.retain(|shot| {
             if shot.y == 1 {
                 return false;
             }
-            let pre_len = aliens.len();
-            aliens.retain(|alien| alien != shot);
-            let destroyed = aliens.len() != pre_len;
-            if destroyed {
-                partial_score += 5;
+            if hit_bunker(bunkers, shot) {
+                return false;
             }
-            !destroyed
+            let Some(index) = aliens.iter().position(|alien| alien.position == *shot) else {
+                return true;
+            };
+            let alien = aliens.remove(index);
+            partial_score += alien.kind.points();
+            events.push(GameEvent::AlienDestroyed(alien));
+            false
         });
-        self.score += partial_score;
+        self.score = self.score.saturating_add(partial_score);
 
         self.spaceship_shots.iter_mut().for_each(|shot| shot.y -= 1);
 
         if self.last_aliens_shots + 5 < frame {
             self.last_aliens_shots = frame;
             for alien in &self.aliens {
-                let must_shot = frame % 66 == 0;
-                let who_shot = alien.x % 6 == 0 && alien.y % 4 == 0;
-                if must_shot && who_shot {
-                    self.aliens_shots.push(*alien);
+                if alien.kind.must_shot(alien.position, frame) {
+                    self.aliens_shots.push(alien.position);
+                    events.push(GameEvent::ShotFired { position: alien.position, by_alien: true });
                 }
             }
 
             let bottom_shot_limit = self.dimension.y;
             self.aliens_shots.retain(|shot| shot.y < bottom_shot_limit);
             self.aliens_shots.iter_mut().for_each(|shot| shot.y += 1);
+
+            let bunkers = &mut self.bunkers;
+            self.aliens_shots.retain(|shot| !hit_bunker(bunkers, shot));
         }
 
         let mut damage = 0;
@@ -122,7 +241,7 @@ impl GameState {
             true
         });
 
-        self.aliens.iter().for_each(|alien| {
+        self.aliens.iter().for_each(|Alien { position: alien, .. }| {
             if alien.y == spaceship.y
                 && (alien.x == spaceship.x
                     || alien.x == spaceship.x + 1
@@ -132,15 +251,16 @@ impl GameState {
             }
         });
 
-        self.lives = if damage >= self.lives {
-            0
-        } else {
-            self.lives - damage
-        };
+        self.lives = self.lives.saturating_sub(damage);
+        if damage > 0 {
+            events.push(GameEvent::PlayerHit { lives: self.lives });
+        }
+        profile::end("collision");
 
-        if self.aliens.len() > 0 {
-            let left = self.aliens.iter().min_by_key(|alien| alien.x).unwrap();
-            let right = self.aliens.iter().max_by_key(|alien| alien.x).unwrap();
+        profile::start("formation");
+        if !self.aliens.is_empty() {
+            let left = self.aliens.iter().map(|alien| alien.position).min_by_key(|alien| alien.x).unwrap();
+            let right = self.aliens.iter().map(|alien| alien.position).max_by_key(|alien| alien.x).unwrap();
             if self.last_aliens_movement + 20 < frame {
                 self.last_aliens_movement = frame;
 
@@ -150,61 +270,185 @@ impl GameState {
                         let dir = self.aliens_movement.0;
                         self.aliens
                             .iter_mut()
-                            .for_each(|alien| alien.x = alien.x + dir);
+                            .for_each(|alien| alien.position.x += dir);
                         self.aliens_movement.1 = false;
                     } else {
-                        self.aliens.iter_mut().for_each(|alien| alien.y += 1);
+                        self.aliens.iter_mut().for_each(|alien| alien.position.y += 1);
                         self.aliens_movement.1 = true;
+                        events.push(GameEvent::FormationDescended);
                     }
                 } else {
                     let dir = self.aliens_movement.0;
                     self.aliens
                         .iter_mut()
-                        .for_each(|alien| alien.x = alien.x + dir);
+                        .for_each(|alien| alien.position.x += dir);
                 }
             }
         }
+
+        let aliens = &self.aliens;
+        self.bunkers.retain(|bunker| aliens.iter().all(|alien| alien.position != *bunker));
+        profile::end("formation");
+
+        if self.aliens.is_empty() || self.lives == 0 || frame + 1 >= MAX_FRAME {
+            events.push(GameEvent::GameOver { win: self.lives > 0 });
+        }
+
+        events
     }
 }
 
+// Removes the bunker block hit by the shot, if any
+fn hit_bunker(bunkers: &mut Vec<Vec2>, shot: &Vec2) -> bool {
+    let pre_len = bunkers.len();
+    bunkers.retain(|bunker| bunker != shot);
+    bunkers.len() != pre_len
+}
+
 // Prove game
-pub fn game_prove(inputs_string: &String) -> GameIO {
-    let user_inputs = hex_string_to_vec(&inputs_string);
-    let mut state = GameState::new(Vec2::xy(60, 22));
-    let mut frame_count = 0;
-    let mut n_user_input = 0;
-
-    while !state.aliens.is_empty() && state.lives > 0 {
-        if frame_count == 1023 {
-            break;
-        }
+pub fn game_prove(inputs_string: &str) -> GameIO {
+    game_prove_level(&Level::classic(Vec2::xy(WIDTH, HEIGHT)), inputs_string)
+}
 
-        if n_user_input < user_inputs.len() {
-            let user_input = user_inputs[n_user_input];
+pub fn game_prove_seed(seed: &str, inputs_string: &str) -> GameIO {
+    let level = generator::generate(&hex_string_to_seed(seed), Vec2::xy(WIDTH, HEIGHT));
+    GameIO {
+        seed: Some(seed.to_string()),
+        ..game_prove_level(&level, inputs_string)
+    }
+}
 
-            if user_input.0 == frame_count as u16 {
-                if user_input.1 == 0 {
-                    state.spaceship_move_x(-1);
-                } else {
-                    state.spaceship_move_x(1);
-                }
-                state.user_input.push((frame_count as u16, user_input.1));
+pub fn game_prove_daily(day: u32, inputs_string: &str) -> GameIO {
+    let seed = seed_to_hex_string(&generator::daily_seed(day));
+    GameIO {
+        day: Some(day),
+        ..game_prove_seed(&seed, inputs_string)
+    }
+}
+
+// Re-simulates the level a GameIO was played on(daily, seeded or classic)
+pub fn game_prove_io(game_i: &GameIO) -> GameIO {
+    match (game_i.day, &game_i.seed) {
+        (Some(day), _) => game_prove_daily(day, &game_i.inputs),
+        (None, Some(seed)) => game_prove_seed(seed, &game_i.inputs),
+        (None, None) => game_prove(&game_i.inputs),
+    }
+}
+
+// Re-simulates a claimed GameIO and checks it field by field,
+// the error is the mismatch the prover program reports
+pub fn verify_io(game_i: &GameIO) -> Result<(), String> {
+    let game_o = game_prove_io(game_i);
+
+    profile::start("compare");
+    let result = compare(game_i, &game_o);
+    profile::end("compare");
+    result
+}
 
-                n_user_input += 1;
+fn compare(game_i: &GameIO, game_o: &GameIO) -> Result<(), String> {
+    if game_i.seed != game_o.seed {
+        return Err(format!("Error: seed doesn't match, {:?}, {:?}", game_i.seed, game_o.seed));
+    }
+
+    if game_i.score != game_o.score {
+        return Err(format!("Error: score doesn't match, {}, {}", game_i.score, game_o.score));
+    }
+    if game_i.win != game_o.win {
+        return Err(format!("Error: win doesn't match, {}, {}", game_i.win, game_o.win));
+    }
+    if game_i.end_frame != game_o.end_frame {
+        return Err(format!("Error: end_frame doesn't match, {}, {}", game_i.end_frame, game_o.end_frame));
+    }
+    if game_i.time_score != game_o.time_score {
+        return Err(format!("Error: time_score doesn't match, {}, {}", game_i.time_score, game_o.time_score));
+    }
+    if game_i.inputs != game_o.inputs {
+        return Err(format!("Error: inputs don't match, {}, {}", game_i.inputs, game_o.inputs));
+    }
+    Ok(())
+}
+
+// The level a GameIO was played on
+pub fn level_of(game_i: &GameIO) -> Level {
+    let dimension = Vec2::xy(WIDTH, HEIGHT);
+    match (game_i.day, &game_i.seed) {
+        (Some(day), _) => generator::generate(&generator::daily_seed(day), dimension),
+        (None, Some(seed)) => generator::generate(&hex_string_to_seed(seed), dimension),
+        (None, None) => Level::classic(dimension),
+    }
+}
+
+pub fn game_prove_level(level: &Level, inputs_string: &str) -> GameIO {
+    let mut simulation = Simulation::new(level, inputs_string);
+    while !simulation.is_over() {
+        simulation.step();
+    }
+    simulation.game_io()
+}
+
+// Runs the engine frame by frame, fed by any input source(replay, keyboard, bot)
+pub struct Simulation<I: InputSource = ReplayInput> {
+    pub state: GameState,
+    pub frame: usize,
+    pub input: I,
+}
+
+impl Simulation<ReplayInput> {
+    pub fn new(level: &Level, inputs_string: &str) -> Simulation {
+        Simulation::with_input(level, ReplayInput::new(inputs_string))
+    }
+}
+
+impl<I: InputSource> Simulation<I> {
+    pub fn with_input(level: &Level, input: I) -> Simulation<I> {
+        Simulation {
+            state: GameState::from_level(Vec2::xy(WIDTH, HEIGHT), level),
+            frame: 0,
+            input,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.state.aliens.is_empty() || self.state.lives == 0 || self.frame == MAX_FRAME
+    }
+
+    pub fn step(&mut self) -> Vec<GameEvent> {
+        if let Some(direction) = self.input.actions(self.frame, &self.state).direction {
+            if direction == 0 {
+                self.state.spaceship_move_x(-1);
+            } else {
+                self.state.spaceship_move_x(1);
             }
+            self.state.user_input.push((self.frame as u16, direction));
         }
 
-        state.spaceship_shot(frame_count);
-        state.update(frame_count);
-        frame_count += 1;
+        let events = self.state.step(self.frame);
+        self.frame += 1;
+        events
     }
 
-    return GameIO {
-        score: state.score,
-        win: state.lives > 0,
-        end_frame: frame_count as u16,
-        inputs: vec_to_hex_string(state.user_input),
-    };
+    pub fn game_io(self) -> GameIO {
+        let win = self.state.lives > 0;
+        GameIO {
+            score: self.state.score,
+            win,
+            end_frame: self.frame as u16,
+            inputs: vec_to_hex_string(self.state.user_input),
+            seed: None,
+            day: None,
+            time_score: time_score(win, self.frame as u16),
+        }
+    }
+}
+
+// Surviving until the frame limit is a win, but it isn't a speedrun
+pub fn time_score(win: bool, end_frame: u16) -> u16 {
+    if win {
+        (MAX_FRAME as u16).saturating_sub(end_frame)
+    } else {
+        0
+    }
 }
 
 pub fn vec_to_hex_string(input: Vec<(u16, u8)>) -> String {
@@ -255,4 +499,117 @@ pub fn hex_string_to_vec(hex_string: &str) -> Vec<(u16, u8)> {
     }
 
     result
-}
\ No newline at end of file
+}
+
+pub fn hex_string_to_seed(hex_string: &str) -> [u8; 32] {
+    if hex_string.len() != 64 {
+        panic!("The seed must be 32 bytes long");
+    }
+
+    let mut seed = [0u8; 32];
+    for (byte, chunk) in seed.iter_mut().zip(hex_string.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(chunk).unwrap(), 16).unwrap();
+    }
+    seed
+}
+
+pub fn seed_to_hex_string(seed: &[u8; 32]) -> String {
+    seed.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input::BotInput;
+
+    // Shared with the tests of the other modules
+    pub(crate) fn bot_run(level: &Level) -> GameIO {
+        let mut simulation = Simulation::with_input(level, BotInput);
+        while !simulation.is_over() {
+            simulation.step();
+        }
+        simulation.game_io()
+    }
+
+    // Sorted input log of pseudo random moves, independent of the engine
+    fn wander(mut state: u32) -> String {
+        let mut inputs = Vec::new();
+        let mut frame = 0;
+        loop {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            frame += 1 + (state >> 16) % 6;
+            if frame >= MAX_FRAME as u32 {
+                return vec_to_hex_string(inputs);
+            }
+            inputs.push((frame as u16, ((state >> 24) & 1) as u8));
+        }
+    }
+
+    fn result(game_io: &GameIO) -> (u8, bool, u16) {
+        (game_io.score, game_io.win, game_io.end_frame)
+    }
+
+    #[test]
+    fn classic_matches_the_baseline_engine() {
+        let classic = Level::classic(Vec2::xy(WIDTH, HEIGHT));
+        let bot = bot_run(&classic);
+        assert_eq!(bot.inputs, "000105810AC10FC11001104115411A811FC0200020402501");
+        assert_eq!(result(&game_prove(&bot.inputs)), result(&bot));
+
+        // Results of the engine before levels, bots and snapshots were added
+        let baseline = [
+            (bot.inputs, (10, false, 157)),
+            (wander(1), (55, false, 425)),
+            (wander(2), (20, true, 1023)),
+            (wander(3), (15, false, 157)),
+            (wander(4), (20, false, 162)),
+            (wander(5), (15, true, 1023)),
+            (wander(6), (35, true, 1023)),
+        ];
+        for (inputs, expected) in baseline {
+            assert_eq!(result(&game_prove(&inputs)), expected, "inputs {}", inputs);
+        }
+    }
+
+    #[test]
+    fn generate_is_deterministic_per_seed() {
+        let dimension = Vec2::xy(WIDTH, HEIGHT);
+        let levels: Vec<Level> = (0..8u8).map(|n| generator::generate(&[n; 32], dimension)).collect();
+        for (n, level) in levels.iter().enumerate() {
+            assert_eq!(*level, generator::generate(&[n as u8; 32], dimension));
+            assert!(!level.aliens.is_empty());
+        }
+        assert!(levels.iter().skip(1).any(|level| *level != levels[0]));
+
+        // A bot run replays to the same result from its seed alone
+        let seed = seed_to_hex_string(&[4u8; 32]);
+        let bot = bot_run(&levels[4]);
+        let replayed = game_prove_seed(&seed, &bot.inputs);
+        assert_eq!(result(&replayed), result(&bot));
+        assert_eq!(replayed.time_score, bot.time_score);
+    }
+
+    #[test]
+    fn legacy_json_gets_its_time_score() {
+        let seed = [2u8; 32];
+        let game_io = bot_run(&generator::generate(&seed, Vec2::xy(WIDTH, HEIGHT)));
+        assert!(game_io.win);
+
+        // A winning run as written before time scores
+        let json = format!(
+            r#"{{"score":{},"win":true,"end_frame":{},"inputs":"{}","seed":"{}"}}"#,
+            game_io.score,
+            game_io.end_frame,
+            game_io.inputs,
+            seed_to_hex_string(&seed)
+        );
+        let legacy: GameIO = serde_json::from_str::<JsonGameIO>(&json).unwrap().into();
+        assert_eq!(legacy.time_score, time_score(true, game_io.end_frame));
+        assert_eq!(verify_io(&legacy), Ok(()));
+
+        // Out of range frames are rejected by the prover, not while reading them
+        let json = r#"{"score":0,"win":true,"end_frame":2000,"inputs":""}"#;
+        let legacy: GameIO = serde_json::from_str::<JsonGameIO>(json).unwrap().into();
+        assert_eq!(legacy.time_score, 0);
+        assert!(verify_io(&legacy).is_err());
+    }
+}
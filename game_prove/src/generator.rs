@@ -0,0 +1,77 @@
+use crate::level::Level;
+use crate::{Alien, AlienKind, Vec2};
+
+// xorshift64, a few instructions per number so it is cheap inside the zkVM
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: &[u8; 32]) -> Rng {
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        for chunk in seed.chunks(8) {
+            state ^= u64::from_le_bytes(chunk.try_into().unwrap());
+            state = state.rotate_left(23).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        }
+        Rng(if state == 0 { 1 } else { state })
+    }
+
+    fn below(&mut self, max: u64) -> i32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % max) as i32
+    }
+}
+
+// The seed of the daily challenge for a UTC day number(unix timestamp / 86400)
+pub fn daily_seed(day: u32) -> [u8; 32] {
+    let mut seed = [0u8; 32];
+    seed[..20].copy_from_slice(b"space-aligners/daily");
+    seed[28..].copy_from_slice(&day.to_be_bytes());
+    seed
+}
+
+// Builds the level of a seed: formation shape, alien kinds and bunkers.
+// At most 20 aliens and one row of elites, so the score always fits in a u8
+pub fn generate(seed: &[u8; 32], dimension: Vec2) -> Level {
+    let mut rng = Rng::new(seed);
+
+    let rows = 2 + rng.below(3);
+    let columns = 3 + rng.below(3);
+    let shape = rng.below(3);
+    let left = 21 + 2 * rng.below(6);
+    let top = 2 + rng.below(3);
+
+    let mut aliens = Vec::new();
+    for row in 0..rows {
+        let kind = match (row, rng.below(3)) {
+            (0, 2) => AlienKind::Elite,
+            (_, 1) => AlienKind::Soldier,
+            _ => AlienKind::Grunt,
+        };
+        for column in 0..columns {
+            let keep = match shape {
+                0 => true,                                        // rectangle
+                1 => (2 * column - columns + 1).abs() <= 2 * row, // pyramid
+                _ => (row + column) % 2 == 0,                     // checkerboard
+            };
+            if keep {
+                aliens.push(Alien::new(Vec2::xy(left + 2 * column, top + row), kind));
+            }
+        }
+    }
+
+    let mut bunkers = Vec::new();
+    let bunkers_count = rng.below(4);
+    for bunker in 0..bunkers_count {
+        let center = dimension.x * (bunker + 1) / (bunkers_count + 1);
+        for x in center - 1..=center + 1 {
+            bunkers.push(Vec2::xy(x, dimension.y - 5));
+        }
+    }
+
+    Level {
+        aliens,
+        bunkers,
+        spaceship: Vec2::xy(dimension.x / 2, dimension.y - 2),
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{hex_string_to_vec, profile, GameState};
+
+// What the player does on a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Actions {
+    pub direction: Option<u8>, // 0 left, 1 right, as in the input log
+}
+
+// Feeds the engine, one call per simulated frame
+pub trait InputSource {
+    fn actions(&mut self, frame: usize, state: &GameState) -> Actions;
+}
+
+// Replays a hex encoded input log, the source of the prover
+pub struct ReplayInput {
+    user_inputs: Vec<(u16, u8)>,
+    n_user_input: usize,
+}
+
+impl ReplayInput {
+    pub fn new(inputs_string: &str) -> ReplayInput {
+        ReplayInput::resume(inputs_string, 0)
+    }
+
+    // Skips the first consumed inputs, to continue a replay mid-game
+    pub fn resume(inputs_string: &str, consumed: usize) -> ReplayInput {
+        profile::start("decode");
+        let user_inputs = hex_string_to_vec(inputs_string);
+        profile::end("decode");
+        ReplayInput {
+            user_inputs,
+            n_user_input: consumed,
+        }
+    }
+}
+
+impl InputSource for ReplayInput {
+    fn actions(&mut self, frame: usize, _state: &GameState) -> Actions {
+        if self.n_user_input < self.user_inputs.len() {
+            let user_input = self.user_inputs[self.n_user_input];
+
+            if user_input.0 == frame as u16 {
+                self.n_user_input += 1;
+                return Actions { direction: Some(user_input.1) };
+            }
+        }
+        Actions::default()
+    }
+}
+
+// Scripted player: moves under the lowest alien, the one closest to the ship
+pub struct BotInput;
+
+impl InputSource for BotInput {
+    fn actions(&mut self, _frame: usize, state: &GameState) -> Actions {
+        let ship = state.spaceship;
+        let target = state.aliens.iter().map(|alien| alien.position).max_by_key(|alien| {
+            (alien.y, -(alien.x - ship.x).abs())
+        });
+        let direction = match target {
+            Some(target) if target.x < ship.x => Some(0),
+            Some(target) if target.x > ship.x => Some(1),
+            _ => None,
+        };
+        Actions { direction }
+    }
+}
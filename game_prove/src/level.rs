@@ -0,0 +1,54 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{Alien, AlienKind, Vec2};
+
+// Starting layout of a game, what the level editor reads and writes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Level {
+    pub aliens: Vec<Alien>,
+    pub bunkers: Vec<Vec2>,
+    pub spaceship: Vec2,
+}
+
+impl Level {
+    // The original formation: 4 rows of 5 aliens and no bunkers
+    pub fn classic(dimension: Vec2) -> Level {
+        let mut aliens = Vec::new();
+        for y in 3..7 {
+            for x in 25..dimension.x - 25 {
+                if x % 2 != 0 {
+                    aliens.push(Alien::new(Vec2::xy(x, y), AlienKind::Grunt));
+                }
+            }
+        }
+        Level {
+            aliens,
+            bunkers: Vec::new(),
+            spaceship: Vec2::xy(dimension.x / 2, dimension.y - 2),
+        }
+    }
+
+    pub fn is_empty(&self, position: Vec2) -> bool {
+        self.aliens.iter().all(|alien| alien.position != position)
+            && !self.bunkers.contains(&position)
+            && self.spaceship != position
+    }
+
+    // Places a grunt on an empty cell, or upgrades the alien already there
+    pub fn cycle_alien(&mut self, position: Vec2) {
+        if let Some(alien) = self.aliens.iter_mut().find(|alien| alien.position == position) {
+            alien.kind = match alien.kind {
+                AlienKind::Grunt => AlienKind::Soldier,
+                AlienKind::Soldier => AlienKind::Elite,
+                AlienKind::Elite => AlienKind::Grunt,
+            };
+        } else if self.is_empty(position) {
+            self.aliens.push(Alien::new(position, AlienKind::Grunt));
+        }
+    }
+
+    pub fn clear(&mut self, position: Vec2) {
+        self.aliens.retain(|alien| alien.position != position);
+        self.bunkers.retain(|bunker| *bunker != position);
+    }
+}
@@ -0,0 +1,14 @@
+// SP1 cycle tracker markers of the engine phases. They are printed only in the profile build
+// of the program, the executor adds up the cycles of each phase in its report
+
+#[inline(always)]
+pub fn start(_phase: &str) {
+    #[cfg(feature = "profile")]
+    println!("cycle-tracker-report-start: {}", _phase);
+}
+
+#[inline(always)]
+pub fn end(_phase: &str) {
+    #[cfg(feature = "profile")]
+    println!("cycle-tracker-report-end: {}", _phase);
+}
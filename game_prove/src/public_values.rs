@@ -0,0 +1,125 @@
+use crate::{hex_string_to_seed, seed_to_hex_string, GameIO};
+
+// The values the program commits, ABI encoded as the verifier contract decodes them:
+// (uint256 score, bool win, uint256 endFrame, bytes inputs, bytes32 seed, uint256 day, uint256 timeScore)
+const WORD: usize = 32;
+const HEAD_WORDS: usize = 7;
+
+fn word(value: u64) -> [u8; WORD] {
+    let mut word = [0u8; WORD];
+    word[WORD - 8..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+// Hex input logs are validated by the re-simulation, a bad one is an empty log here
+fn hex_to_bytes(hex_string: &str) -> Vec<u8> {
+    hex_string
+        .as_bytes()
+        .chunks(2)
+        .map(|chunk| std::str::from_utf8(chunk).ok().and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .unwrap_or_default()
+}
+
+// No seed and no day are encoded as zero
+pub fn encode(game_io: &GameIO) -> Vec<u8> {
+    let inputs = hex_to_bytes(&game_io.inputs);
+    let seed = game_io.seed.as_deref().map(hex_string_to_seed).unwrap_or_default();
+
+    let mut encoded = Vec::with_capacity((HEAD_WORDS + 2) * WORD + inputs.len());
+    encoded.extend(word(game_io.score.into()));
+    encoded.extend(word(game_io.win.into()));
+    encoded.extend(word(game_io.end_frame.into()));
+    encoded.extend(word((HEAD_WORDS * WORD) as u64)); // offset of inputs
+    encoded.extend(seed);
+    encoded.extend(word(game_io.day.unwrap_or_default().into()));
+    encoded.extend(word(game_io.time_score.into()));
+
+    encoded.extend(word(inputs.len() as u64));
+    encoded.extend(&inputs);
+    encoded.resize(encoded.len().next_multiple_of(WORD), 0);
+    encoded
+}
+
+// Reads a word that must fit in a u64, with the rest of it zero
+fn read_word(encoded: &[u8], index: usize) -> Result<u64, String> {
+    let word = encoded
+        .get(index * WORD..(index + 1) * WORD)
+        .ok_or("public values are too short")?;
+    if word[..WORD - 8].iter().any(|byte| *byte != 0) {
+        return Err(format!("word {} is out of range", index));
+    }
+    Ok(u64::from_be_bytes(word[WORD - 8..].try_into().unwrap()))
+}
+
+pub fn decode(encoded: &[u8]) -> Result<GameIO, String> {
+    let narrow = |index: usize| -> Result<u16, String> {
+        u16::try_from(read_word(encoded, index)?).map_err(|_| format!("word {} is out of range", index))
+    };
+    let score = u8::try_from(read_word(encoded, 0)?).map_err(|_| "score is out of range")?;
+    let win = match read_word(encoded, 1)? {
+        0 => false,
+        1 => true,
+        _ => return Err("win is not a bool".to_string()),
+    };
+    let end_frame = narrow(2)?;
+    if read_word(encoded, 3)? != (HEAD_WORDS * WORD) as u64 {
+        return Err("unexpected offset of the inputs".to_string());
+    }
+    let seed: [u8; 32] = encoded
+        .get(4 * WORD..5 * WORD)
+        .ok_or("public values are too short")?
+        .try_into()
+        .unwrap();
+    let day = u32::try_from(read_word(encoded, 5)?).map_err(|_| "day is out of range")?;
+    let time_score = narrow(6)?;
+
+    let length = read_word(encoded, HEAD_WORDS)? as usize;
+    let start = (HEAD_WORDS + 1) * WORD;
+    let inputs = encoded.get(start..).and_then(|rest| rest.get(..length)).ok_or("inputs are too short")?;
+
+    Ok(GameIO {
+        score,
+        win,
+        end_frame,
+        // Same case as vec_to_hex_string, the prover compares the strings
+        inputs: inputs.iter().map(|byte| format!("{:02X}", byte)).collect(),
+        seed: (seed != [0u8; 32]).then(|| seed_to_hex_string(&seed)),
+        day: (day != 0).then_some(day),
+        time_score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game_prove, game_prove_daily};
+
+    #[test]
+    fn round_trip() {
+        for game_io in [game_prove("0440054006800701"), game_prove_daily(20000, "0101")] {
+            let decoded = decode(&encode(&game_io)).unwrap();
+            assert_eq!(decoded.score, game_io.score);
+            assert_eq!(decoded.win, game_io.win);
+            assert_eq!(decoded.end_frame, game_io.end_frame);
+            assert_eq!(decoded.inputs, game_io.inputs);
+            assert_eq!(decoded.seed, game_io.seed);
+            assert_eq!(decoded.day, game_io.day);
+            assert_eq!(decoded.time_score, game_io.time_score);
+        }
+    }
+
+    #[test]
+    fn abi_layout() {
+        let game_io = game_prove("0440");
+        let encoded = encode(&game_io);
+        // 7 head words, the inputs length and one padded word of inputs
+        assert_eq!(encoded.len(), 9 * WORD);
+        assert_eq!(read_word(&encoded, 3).unwrap(), 224);
+        assert_eq!(read_word(&encoded, 7).unwrap(), 2);
+        assert_eq!(&encoded[8 * WORD..8 * WORD + 3], &[0x04, 0x40, 0x00]);
+        assert!(decode(&encoded[..8 * WORD]).is_err());
+        // Cut inside the seed, after the offset word was read
+        assert!(decode(&encoded[..4 * WORD + 12]).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{level_of, GameIO};
+
+// Bumped on every incompatible change of the replay file
+pub const REPLAY_VERSION: u32 = 1;
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// The result the player claims, checked by the prover
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claimed {
+    pub score: u8,
+    pub win: bool,
+    pub end_frame: u16,
+    pub time_score: u16,
+}
+
+// Self-describing record of a run, everything needed to replay, prove and submit it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub engine_version: String,
+    // Hex encoded Level::level_hash of the level the run was played on
+    pub level_hash: String,
+    pub seed: Option<String>,
+    pub day: Option<u32>,
+    // Address of the wallet that played, if any
+    pub player: Option<String>,
+    // Unix seconds at the end of the run
+    pub timestamp: u64,
+    pub inputs: String,
+    pub claimed: Claimed,
+}
+
+impl Replay {
+    pub fn new(game_io: &GameIO, player: Option<String>, timestamp: u64) -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+            engine_version: ENGINE_VERSION.to_string(),
+            level_hash: format!("{:016x}", level_of(game_io).level_hash()),
+            seed: game_io.seed.clone(),
+            day: game_io.day,
+            player,
+            timestamp,
+            inputs: game_io.inputs.clone(),
+            claimed: Claimed {
+                score: game_io.score,
+                win: game_io.win,
+                end_frame: game_io.end_frame,
+                time_score: game_io.time_score,
+            },
+        }
+    }
+
+    pub fn game_io(&self) -> GameIO {
+        GameIO {
+            score: self.claimed.score,
+            win: self.claimed.win,
+            end_frame: self.claimed.end_frame,
+            inputs: self.inputs.clone(),
+            seed: self.seed.clone(),
+            day: self.day,
+            time_score: self.claimed.time_score,
+        }
+    }
+
+    // A newer format can't be read, and the seed or day must still describe the same level
+    pub fn check(&self) -> Result<(), String> {
+        if self.version > REPLAY_VERSION {
+            return Err(format!(
+                "replay version {} is newer than the supported {}",
+                self.version, REPLAY_VERSION
+            ));
+        }
+        let level_hash = format!("{:016x}", level_of(&self.game_io()).level_hash());
+        if level_hash != self.level_hash {
+            return Err(format!(
+                "level hash {} doesn't match the level of the seed, {}",
+                self.level_hash, level_hash
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Serialize, Deserialize};
+
+use crate::input::{InputSource, ReplayInput};
+use crate::level::Level;
+use crate::{Alien, AlienKind, GameState, Simulation, Vec2};
+
+// A mid-game state, the frame is the next one to simulate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub frame: usize,
+    pub state: GameState,
+}
+
+impl<I: InputSource> Simulation<I> {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            frame: self.frame,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Simulation<ReplayInput> {
+    // Continues the input log from a snapshot, skipping the inputs the state already consumed
+    pub fn restore(snapshot: Snapshot, inputs_string: &str) -> Simulation {
+        let consumed = snapshot.state.user_input.len();
+        Simulation {
+            state: snapshot.state,
+            frame: snapshot.frame,
+            input: ReplayInput::resume(inputs_string, consumed),
+        }
+    }
+}
+
+// FNV-1a, no dependencies and cheap inside the zkVM
+struct Hasher(u64);
+
+impl Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01B3);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_vec2(&mut self, value: &Vec2) {
+        self.write(&value.x.to_le_bytes());
+        self.write(&value.y.to_le_bytes());
+    }
+
+    fn write_vec2s(&mut self, values: &[Vec2]) {
+        self.write_u64(values.len() as u64);
+        values.iter().for_each(|value| self.write_vec2(value));
+    }
+
+    fn write_aliens(&mut self, aliens: &[Alien]) {
+        self.write_u64(aliens.len() as u64);
+        for alien in aliens {
+            self.write_vec2(&alien.position);
+            self.write(&[match alien.kind {
+                AlienKind::Grunt => 0,
+                AlienKind::Soldier => 1,
+                AlienKind::Elite => 2,
+            }]);
+        }
+    }
+}
+
+impl GameState {
+    // Canonical hash of every field, in declaration order and with little endian integers,
+    // so the live game and the re-simulation can be compared frame by frame
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = Hasher(0xCBF2_9CE4_8422_2325);
+        hasher.write_vec2(&self.dimension);
+        hasher.write_vec2(&self.spaceship);
+        hasher.write_vec2s(&self.spaceship_shots);
+        hasher.write_u64(self.last_shot_frame as u64);
+        hasher.write_aliens(&self.aliens);
+        hasher.write_vec2s(&self.aliens_shots);
+        hasher.write_vec2s(&self.bunkers);
+        hasher.write(&self.aliens_movement.0.to_le_bytes());
+        hasher.write(&[self.aliens_movement.1 as u8]);
+        hasher.write_u64(self.last_aliens_movement as u64);
+        hasher.write_u64(self.last_aliens_shots as u64);
+        hasher.write_u64(self.lives as u64);
+        hasher.write(&[self.score]);
+        hasher.write_u64(self.user_input.len() as u64);
+        for (frame, direction) in &self.user_input {
+            hasher.write(&frame.to_le_bytes());
+            hasher.write(&[*direction]);
+        }
+        hasher.0
+    }
+}
+
+impl Level {
+    // Canonical hash of the starting board, identifies the level a replay was played on
+    pub fn level_hash(&self) -> u64 {
+        let mut hasher = Hasher(0xCBF2_9CE4_8422_2325);
+        hasher.write_aliens(&self.aliens);
+        hasher.write_vec2s(&self.bunkers);
+        hasher.write_vec2(&self.spaceship);
+        hasher.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::bot_run;
+    use crate::{generator, public_values, HEIGHT, WIDTH};
+
+    #[test]
+    fn restore_continues_the_run() {
+        let level = generator::generate(&[0u8; 32], Vec2::xy(WIDTH, HEIGHT));
+        let inputs = bot_run(&level).inputs;
+
+        let mut simulation = Simulation::new(&level, &inputs);
+        let mut hashes = vec![simulation.state.state_hash()];
+        while !simulation.is_over() {
+            simulation.step();
+            hashes.push(simulation.state.state_hash());
+        }
+        let expected = simulation.game_io();
+
+        // Snapshot halfway through, after some of the inputs are consumed
+        let mut simulation = Simulation::new(&level, &inputs);
+        while simulation.frame < hashes.len() / 2 {
+            simulation.step();
+        }
+        let snapshot = simulation.snapshot();
+        assert!(!snapshot.state.user_input.is_empty());
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let mut restored = Simulation::restore(serde_json::from_str(&json).unwrap(), &inputs);
+        assert_eq!(restored.state.state_hash(), hashes[restored.frame]);
+        while !restored.is_over() {
+            restored.step();
+            assert_eq!(restored.state.state_hash(), hashes[restored.frame], "frame {}", restored.frame);
+        }
+        // Every field of the result, as the program commits it
+        assert_eq!(public_values::encode(&restored.game_io()), public_values::encode(&expected));
+    }
+}
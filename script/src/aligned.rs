@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aligned_sdk::core::types::{
+    AlignedVerificationData, Network, PriceEstimate, ProvingSystemId, VerificationData,
+};
+use aligned_sdk::sdk::{deposit_to_aligned, estimate_fee};
+use aligned_sdk::sdk::{get_next_nonce, submit, submit_and_wait_verification};
+use dialoguer::Confirm;
+use ethers::prelude::*;
+use ethers::providers::{Http, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::prove::{self, ELF};
+
+abigen!(LeaderBoardVerifierContract, "../contracts/out/LeaderBoardVerifierContract.sol/LeaderBoardVerifierContract.json",);
+
+pub type Client = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+// Everything the claim needs, kept so a failed claim can be retried without paying Aligned again
+#[derive(Serialize, Deserialize)]
+pub struct ClaimFile {
+    pub aligned_verification_data: AlignedVerificationData,
+    // Hex encoded, the same bytes submitted to Aligned
+    pub pub_input: String,
+}
+
+pub async fn deposit(signer: &Client, network: Network) {
+    if Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt("Do you want to deposit 0.004eth in Aligned ?\nIf you already deposited Ethereum to Aligned before, this is not needed")
+        .interact()
+        .expect("Failed to read user input") {
+            deposit_to_aligned(U256::from(4000000000000000u128), signer.clone(), network).await
+                .expect("Failed to pay for proof submission");
+        }
+}
+
+// Fee offered to the mock batcher, it charges nothing
+const MOCK_MAX_FEE: u128 = 1_000_000_000_000_000;
+
+// Submits a proof file to Aligned and writes the claim file once it's verified,
+// None if the player declines the fee or the claim file can't be written. The mock batcher
+// has no payment contract and no on-chain verification, so the fee is fixed, the nonce is
+// its first one and it returns on the batch inclusion
+pub async fn submit_proof(
+    proof_path: &Path,
+    wallet: &LocalWallet,
+    rpc_url: &str,
+    batcher_url: &str,
+    network: Network,
+    mock_batcher: bool,
+) -> Option<PathBuf> {
+    let proof_file = prove::load(proof_path);
+    let encoded_vec = proof_file.pub_input;
+
+    let verification_data = VerificationData {
+        proving_system: ProvingSystemId::SP1,
+        proof: proof_file.proof,
+        proof_generator_addr: wallet.address(),
+        vm_program_code: Some(ELF.to_vec()),
+        verification_key: None,
+        pub_input: Some(encoded_vec.clone()),
+    };
+
+    let aligned_verification_data = if mock_batcher {
+        println!("Submitting your proof to the mock batcher...");
+        submit(batcher_url, network, &verification_data, U256::from(MOCK_MAX_FEE), wallet.clone(), U256::zero())
+            .await
+            .unwrap()
+    } else {
+        let max_fee = estimate_fee(rpc_url, PriceEstimate::Instant)
+            .await
+            .expect("failed to fetch gas price from the blockchain");
+
+        let max_fee_string = ethers::utils::format_units(max_fee, 18).unwrap();
+
+        if !Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt(format!("Aligned will use at most {max_fee_string} eth to verify your proof. Do you want to continue?"))
+            .interact()
+            .expect("Failed to read user input")
+        {   return None; }
+
+        let nonce = get_next_nonce(rpc_url, wallet.address(), network)
+            .await
+            .expect("Failed to get next nonce");
+
+        println!("Submitting your proof...");
+
+        submit_and_wait_verification(
+            batcher_url,
+            rpc_url,
+            network,
+            &verification_data,
+            max_fee,
+            wallet.clone(),
+            nonce,
+        )
+        .await
+        .unwrap()
+    };
+
+    // Saved before anything else, the verification is already paid. Next to the proof file,
+    // whose folder exists even when the proof was copied from another machine
+    let claim_file = ClaimFile {
+        aligned_verification_data,
+        pub_input: hex::encode(encoded_vec),
+    };
+    let json = serde_json::to_string(&claim_file).unwrap();
+    let path = proof_path.with_extension("claim.json");
+
+    println!(
+        "Proof submitted and verified successfully on batch {}",
+        hex::encode(claim_file.aligned_verification_data.batch_merkle_root)
+    );
+    if let Err(error) = fs::write(&path, &json) {
+        // The data can't be fetched again, so it's printed to be saved by hand
+        println!("Failed to write the claim file {}: {}", path.display(), error);
+        println!("Save this as a claim file to claim the NFT later:\n{}", json);
+        return None;
+    }
+    println!("Claim file saved to {}", path.display());
+    Some(path)
+}
+
+// Claims the NFT of a verified proof from its claim file
+pub async fn claim(
+    claim_path: &Path,
+    signer: Client,
+    leaderboard_verifier_contract_addr: &Address,
+) -> anyhow::Result<()> {
+    let claim_file: ClaimFile = serde_json::from_str(&fs::read_to_string(claim_path)?)?;
+
+    claim_nft_with_verified_proof(
+        &claim_file.aligned_verification_data,
+        hex::decode(&claim_file.pub_input)?,
+        signer,
+        leaderboard_verifier_contract_addr,
+    )
+    .await
+}
+
+async fn claim_nft_with_verified_proof(
+    aligned_verification_data: &AlignedVerificationData,
+    encoded_vec: Vec<u8>,
+    signer: Client,
+    leaderboard_verifier_contract_addr: &Address,
+) -> anyhow::Result<()> {
+    let leaderboard_verifier_contract = LeaderBoardVerifierContract::new(*leaderboard_verifier_contract_addr, signer.into());
+
+    let index_in_batch = U256::from(aligned_verification_data.index_in_batch);
+    let merkle_path = Bytes::from(
+        aligned_verification_data
+            .batch_inclusion_proof
+            .merkle_path
+            .as_slice()
+            .concat()
+            .to_vec(),
+    );
+
+    let receipt = leaderboard_verifier_contract
+        .verify_batch_inclusion(
+            aligned_verification_data
+                .verification_data_commitment
+                .proof_commitment,
+            aligned_verification_data
+                .verification_data_commitment
+                .pub_input_commitment,
+            aligned_verification_data
+                .verification_data_commitment
+                .proving_system_aux_data_commitment,
+            aligned_verification_data
+                .verification_data_commitment
+                .proof_generator_addr,
+            aligned_verification_data.batch_merkle_root,
+            merkle_path,
+            index_in_batch,
+            encoded_vec.into(),
+        )
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to send tx {}", e))?
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to submit tx {}", e))?;
+
+    match receipt {
+        Some(receipt) => {
+            println!(
+                "Prize claimed successfully. Transaction hash: {:x}",
+                receipt.transaction_hash
+            );
+            Ok(())
+        }
+        None => {
+            anyhow::bail!("Failed to claim prize: no receipt");
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use game_prove::{GameIO, HEIGHT, WIDTH};
+
+use crate::render::{Renderer, Tint, View};
+use crate::replay;
+use crate::ticker::TICKS_PER_SECOND;
+
+// Renders each view as the ANSI output of an asciinema v2 event
+#[derive(Default)]
+pub struct CastRenderer {
+    pub events: Vec<(f64, String)>,
+}
+
+// Same colours as the ruscii renderer, as ANSI foreground codes
+fn ansi(tint: Tint) -> u8 {
+    match tint {
+        Tint::Red => 31,
+        Tint::Green => 32,
+        Tint::Yellow => 33,
+        Tint::Blue => 34,
+        Tint::Magenta => 35,
+        Tint::Cyan => 36,
+        Tint::White => 37,
+        Tint::Grey => 90,
+    }
+}
+
+impl Renderer for CastRenderer {
+    fn draw(&mut self, view: &View) {
+        // Home and clear, then the status where the ruscii renderer puts it
+        let mut output = format!("\x1b[H\x1b[2J\x1b[0m\x1b[1;16H{}", view.status);
+        for glyph in view.glyphs() {
+            if glyph.position.x < 0 || glyph.position.y < 0 {
+                continue;
+            }
+            output.push_str(&format!(
+                "\x1b[{};{}H\x1b[{};{}m{}",
+                glyph.position.y + 1,
+                glyph.position.x + 1,
+                if glyph.bold { 1 } else { 0 },
+                ansi(glyph.tint),
+                glyph.symbol
+            ));
+        }
+        output.push_str("\x1b[0m");
+
+        // Frames without visible changes are left out of the recording
+        if self.events.last().map(|(_, last)| last) == Some(&output) {
+            return;
+        }
+        let time = view.frame as f64 / TICKS_PER_SECOND as f64;
+        self.events.push((time, output));
+    }
+}
+
+// Re-simulates the run and writes it as an asciinema v2 recording, at the live speed
+pub fn export(game_io: &GameIO, path: &Path) {
+    let mut renderer = CastRenderer::default();
+    for snapshot in replay::trace(game_io) {
+        renderer.draw(&View::new(&snapshot.state, snapshot.frame));
+    }
+
+    let header = json!({
+        "version": 2,
+        "width": WIDTH + 2,
+        "height": HEIGHT + 1,
+        "timestamp": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+        "title": format!(
+            "Space Aligners - score {}, win {}, end frame {}",
+            game_io.score, game_io.win, game_io.end_frame
+        ),
+    });
+    let mut cast = header.to_string();
+    for (time, output) in renderer.events {
+        cast.push('\n');
+        cast.push_str(&json!([time, "o", output]).to_string());
+    }
+    cast.push('\n');
+
+    fs::write(path, cast).expect("Failed to write cast file");
+    println!("Recording saved to {}", path.display());
+}
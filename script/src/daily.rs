@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use game_prove::{generator, seed_to_hex_string, AlienKind, GameIO, HEIGHT, WIDTH};
+
+use crate::replay;
+
+const REPLAYS_FOLDER: &str = "replays/daily";
+
+// UTC day number, the same the contract gets from block.timestamp / 1 days
+pub fn today() -> u32 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    (now.as_secs() / 86400) as u32
+}
+
+// Replays of daily runs are grouped by day
+pub fn day_folder(day: u32) -> PathBuf {
+    PathBuf::from(REPLAYS_FOLDER).join(day.to_string())
+}
+
+pub fn list(day: u32) {
+    let seed = generator::daily_seed(day);
+    let level = generator::generate(&seed, game_prove::Vec2::xy(WIDTH, HEIGHT));
+    let count = |kind| level.aliens.iter().filter(|alien| alien.kind == kind).count();
+
+    println!("Daily challenge of day {}", day);
+    println!("Seed: {}", seed_to_hex_string(&seed));
+    println!(
+        "Grunts: {}, soldiers: {}, elites: {}, bunker blocks: {}",
+        count(AlienKind::Grunt),
+        count(AlienKind::Soldier),
+        count(AlienKind::Elite),
+        level.bunkers.len()
+    );
+
+    let Ok(entries) = fs::read_dir(day_folder(day)) else {
+        println!("No local runs today");
+        return;
+    };
+    let mut runs: Vec<(String, GameIO)> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let game_io = replay::parse(&fs::read_to_string(&path).ok()?).ok()?;
+            Some((path.display().to_string(), game_io))
+        })
+        .collect();
+    runs.sort_by(|a, b| b.1.score.cmp(&a.1.score).then(a.1.end_frame.cmp(&b.1.end_frame)));
+
+    println!("Local runs today:");
+    for (path, run) in runs {
+        println!(
+            "  score {}, win {}, end frame {}: {}",
+            run.score, run.win, run.end_frame, path
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use game_prove::level::Level;
+use game_prove::snapshot::Snapshot;
+use game_prove::{GameIO, Simulation};
+
+use crate::render::{Renderer, TextRenderer, View};
+
+// First frame where the live game and the game_prove re-simulation disagree.
+// A missing snapshot means that side of the run had already ended
+pub struct Divergence {
+    pub frame: usize,
+    pub live: Option<Snapshot>,
+    pub proof: Option<Snapshot>,
+}
+
+pub fn first_divergence(level: &Level, game_io: &GameIO, live_trace: &[Snapshot]) -> Option<Divergence> {
+    let mut simulation = Simulation::new(level, &game_io.inputs);
+    let mut live_trace = live_trace.iter();
+
+    for frame in 0.. {
+        let proof = (!simulation.is_over()).then(|| {
+            simulation.step();
+            simulation.snapshot()
+        });
+        let live = live_trace.next();
+
+        match (live, proof) {
+            (None, None) => return None,
+            (Some(live), Some(proof)) if live.state.state_hash() == proof.state.state_hash() => (),
+            (live, proof) => {
+                return Some(Divergence {
+                    frame,
+                    live: live.cloned(),
+                    proof,
+                })
+            }
+        }
+    }
+    unreachable!()
+}
+
+pub fn report(divergence: &Divergence) {
+    println!("The live game and the proof re-simulation diverge on frame {}", divergence.frame);
+    for (side, snapshot) in [("Live game", &divergence.live), ("Proof", &divergence.proof)] {
+        match snapshot {
+            Some(snapshot) => {
+                let mut renderer = TextRenderer::default();
+                renderer.draw(&View::new(&snapshot.state, snapshot.frame));
+                println!(
+                    "{} state(hash {:016x}):\n{}{}",
+                    side,
+                    snapshot.state.state_hash(),
+                    renderer.frames[0],
+                    serde_json::to_string_pretty(&snapshot.state).unwrap()
+                );
+            }
+            None => println!("{} already ended", side),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::Path;
+
+use ruscii::app::{App, State};
+use ruscii::keyboard::{Key, KeyEvent};
+use ruscii::spatial::Vec2;
+use ruscii::terminal::{Color, Style, Window};
+
+use game_prove::level::Level;
+use game_prove::{game_prove_level, GameState, HEIGHT, MAX_FRAME, WIDTH};
+
+use crate::game;
+use crate::input::Bindings;
+use crate::render::{screen, Renderer, RusciiRenderer, View};
+
+pub fn edit(level_path: &Path, bindings: Bindings) {
+    let level = match fs::read_to_string(level_path) {
+        Ok(json) => serde_json::from_str(&json).expect("Failed to parse level file"),
+        Err(_) => Level::classic(game_prove::Vec2::xy(WIDTH, HEIGHT)),
+    };
+
+    let Some(level) = level_editor(level) else {
+        println!("Level discarded");
+        return;
+    };
+
+    fs::write(level_path, serde_json::to_string(&level).unwrap())
+        .expect("Failed to write level file");
+    println!("Level saved to {}", level_path.display());
+
+    // Without user inputs the formation must reach the ship before the frame limit
+    let dry_run = game_prove_level(&level, "");
+    println!(
+        "Dry run: score {}, win {}, end frame {}",
+        dry_run.score, dry_run.win, dry_run.end_frame
+    );
+    if dry_run.end_frame as usize >= MAX_FRAME {
+        println!("Warning: the level doesn't end within {} frames", MAX_FRAME);
+    }
+
+    let (game_o, _) = game::game_play(&level, None, None, bindings);
+    println!(
+        "Test play: score {}, win {}, end frame {}",
+        game_o.score, game_o.win, game_o.end_frame
+    );
+}
+
+// Returns the edited level, or None if the user quits without saving
+fn level_editor(mut level: Level) -> Option<Level> {
+    let mut app = App::default();
+    let mut cursor = level.spaceship;
+    let mut saved = false;
+    // Set when saving a level without aliens, the dry run would be a win at frame 0
+    let mut no_aliens = false;
+
+    app.run(|app_state: &mut State, window: &mut Window| {
+        for key_event in app_state.keyboard().last_key_events() {
+            match key_event {
+                KeyEvent::Pressed(Key::Esc) | KeyEvent::Pressed(Key::Q) => {
+                    app_state.stop();
+                    return;
+                }
+                KeyEvent::Pressed(Key::Enter) if level.aliens.is_empty() => no_aliens = true,
+                KeyEvent::Pressed(Key::Enter) => {
+                    saved = true;
+                    app_state.stop();
+                    return;
+                }
+                // Same bounds as the engine, the first row is left to the status
+                KeyEvent::Pressed(Key::Left) if cursor.x > 0 => cursor.x -= 1,
+                KeyEvent::Pressed(Key::Right) if cursor.x < WIDTH => cursor.x += 1,
+                KeyEvent::Pressed(Key::Up) if cursor.y > 1 => cursor.y -= 1,
+                KeyEvent::Pressed(Key::Down) if cursor.y < HEIGHT => cursor.y += 1,
+                KeyEvent::Pressed(Key::A) => level.cycle_alien(cursor),
+                KeyEvent::Pressed(Key::B) if level.is_empty(cursor) => level.bunkers.push(cursor),
+                KeyEvent::Pressed(Key::S) if level.is_empty(cursor) => level.spaceship = cursor,
+                KeyEvent::Pressed(Key::X) => level.clear(cursor),
+                _ => (),
+            }
+        }
+
+        let state = GameState::from_level(game_prove::Vec2::xy(WIDTH, HEIGHT), &level);
+        let mut view = View::new(&state, 0);
+        view.status = format!("aliens: {}  -  bunkers: {}", level.aliens.len(), level.bunkers.len());
+        if no_aliens && level.aliens.is_empty() {
+            view.status.push_str("  -  place at least one alien to save");
+        }
+
+        let mut renderer = RusciiRenderer::new(window);
+        renderer.pencil().draw_text(
+            "arrows: move  a: alien/kind  b: bunker  s: ship  x: erase  enter: save  q: quit",
+            Vec2::xy(1, 0),
+        );
+        renderer.draw(&view);
+        renderer
+            .pencil()
+            .set_foreground(Color::Yellow)
+            .set_style(Style::Bold)
+            .draw_char('+', screen(cursor));
+    });
+
+    if saved {
+        Some(level)
+    } else {
+        None
+    }
+}
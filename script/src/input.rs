@@ -0,0 +1,73 @@
+use clap::ValueEnum;
+use ruscii::keyboard::{Key, KeyEvent};
+
+use game_prove::input::{Actions, InputSource};
+use game_prove::GameState;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Bindings {
+    Arrows,
+    Wasd,
+    Vim,
+}
+
+impl Bindings {
+    fn keys(&self) -> (Key, Key) {
+        match self {
+            Bindings::Arrows => (Key::Left, Key::Right),
+            Bindings::Wasd => (Key::A, Key::D),
+            Bindings::Vim => (Key::H, Key::L),
+        }
+    }
+}
+
+// Press and release state of the movement keys, independent of the OS key repeat
+pub struct Input {
+    bindings: Bindings,
+    held: [bool; 2],
+    // Pressed since the last tick, so a tap shorter than a tick still moves once
+    tapped: [bool; 2],
+}
+
+impl Input {
+    pub fn new(bindings: Bindings) -> Input {
+        Input {
+            bindings,
+            held: [false; 2],
+            tapped: [false; 2],
+        }
+    }
+
+    pub fn handle(&mut self, key_event: &KeyEvent) {
+        let (left, right) = self.bindings.keys();
+        let (key, pressed) = match key_event {
+            KeyEvent::Pressed(key) => (key, true),
+            KeyEvent::Released(key) => (key, false),
+        };
+        let direction = if *key == left {
+            0
+        } else if *key == right {
+            1
+        } else {
+            return;
+        };
+        self.held[direction] = pressed;
+        self.tapped[direction] |= pressed;
+    }
+}
+
+impl InputSource for Input {
+    // The one action of a tick for the game_prove input log,
+    // no move when no direction or both are active
+    fn actions(&mut self, _frame: usize, _state: &GameState) -> Actions {
+        let left = self.held[0] || self.tapped[0];
+        let right = self.held[1] || self.tapped[1];
+        self.tapped = [false; 2];
+        let direction = match (left, right) {
+            (true, false) => Some(0),
+            (false, true) => Some(1),
+            _ => None,
+        };
+        Actions { direction }
+    }
+}
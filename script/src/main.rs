@@ -1,247 +1,452 @@
-use aligned_sdk::core::types::{
-    AlignedVerificationData, Network, PriceEstimate, ProvingSystemId, VerificationData,
-};
-use aligned_sdk::sdk::{deposit_to_aligned, estimate_fee};
-use aligned_sdk::sdk::{get_next_nonce, submit_and_wait_verification};
-use clap::Parser;
+use aligned_sdk::core::types::Network;
+use clap::{Parser, Subcommand};
 use dialoguer::Confirm;
 use ethers::prelude::*;
 use ethers::providers::{Http, Provider};
 use ethers::signers::{LocalWallet, Signer};
-use ethers::types::{Address, Bytes, H160, U256};
-use sp1_sdk::{ProverClient, SP1Stdin};
-use ethers::abi::{encode, Token};
-use serde::{Deserialize, Serialize};
-use hex;
+use ethers::types::{H160, U256};
+use std::path::{Path, PathBuf};
 
 // game.rs
 mod game;
-use game_prove::{GameIO};
-
-abigen!(LeaderBoardVerifierContract, "../contracts/out/LeaderBoardVerifierContract.sol/LeaderBoardVerifierContract.json",);
-
-const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
-
-#[derive(Serialize, Deserialize)]
-struct PubInput {
-    score: u8,
-    win: bool,
-    end_frame: u16,
-    inputs: Vec<u8>,
-}
-impl PubInput {
-    pub fn encode_to_vec(&self) -> Vec<u8> {
-        // Convert struct fields into ABI-encodable tokens
-        let tokens = vec![
-            Token::Uint(self.score.into()),
-            Token::Uint(U256::from(if self.win { 1 } else { 0 })),
-            Token::Uint(self.end_frame.into()),
-            Token::Bytes(self.inputs.clone()),
-        ];
-
-        // Encode the tokens into bytes
-        Bytes::from(encode(&tokens)).to_vec()
-    }
-}
+// editor.rs
+mod editor;
+// daily.rs
+mod daily;
+// personal_best.rs
+mod personal_best;
+// splits.rs
+mod splits;
+// divergence.rs
+mod divergence;
+// ticker.rs
+mod ticker;
+// input.rs
+mod input;
+// render.rs
+mod render;
+// replay.rs
+mod replay;
+// cast.rs
+mod cast;
+// svg.rs
+mod svg;
+// session.rs
+mod session;
+// prove.rs
+mod prove;
+// aligned.rs
+mod aligned;
+// profile.rs
+mod profile;
+// mock_batcher.rs
+mod mock_batcher;
+use game_prove::{GameIO, HEIGHT, WIDTH, hex_string_to_seed, seed_to_hex_string};
+use game_prove::level::Level;
+use game_prove::generator;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
-    #[arg(short, long)]
-    keystore_path: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+    // Required to play, prove and submit without a subcommand, the subcommands that go on chain take their own
+    #[arg(short, long, required = true)]
+    keystore_path: Option<String>,
     #[arg(
         short,
         long,
+        global = true,
         default_value = "https://ethereum-holesky-rpc.publicnode.com"
     )]
     rpc_url: String,
-    #[arg(short, long, default_value = "wss://batcher.alignedlayer.com")]
+    #[arg(short, long, global = true, default_value = "wss://batcher.alignedlayer.com")]
     batcher_url: String,
-    #[arg(short, long, default_value = "holesky")]
+    #[arg(short, long, global = true, default_value = "holesky")]
     network: Network,
-    #[arg(short, long)]
-    leaderboard_verifier_contract_address: H160,
+    #[arg(short, long, required_unless_present = "mock_batcher")]
+    leaderboard_verifier_contract_address: Option<H160>,
+    /// Hex encoded 32 bytes seed to play a generated level instead of the classic one
+    #[arg(short, long, global = true, value_parser = parse_seed)]
+    seed: Option<String>,
+    /// Replay file or claim hex pub input of a run of the same level to race against
+    #[arg(short, long, global = true)]
+    ghost: Option<String>,
+    /// Replay file of a run to prove and submit, instead of playing
+    #[arg(long)]
+    replay_file: Option<PathBuf>,
+    /// SP1 prover, mock skips the proving for fast end-to-end tests. Defaults to SP1_PROVER
+    #[arg(long, global = true, value_enum)]
+    prover: Option<prove::Prover>,
+    /// The batcher URL is a local mock batcher: no deposit, no RPC calls and nothing to claim
+    #[arg(long, global = true)]
+    mock_batcher: bool,
+    /// Movement keys
+    #[arg(long, global = true, value_enum, default_value = "wasd")]
+    bindings: input::Bindings,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Play a run and save its replay, to prove it later
+    Play {
+        /// Play today's daily challenge
+        #[arg(long)]
+        daily: bool,
+    },
+    /// Prove the run of a replay file and save the proof
+    Prove {
+        replay: PathBuf,
+    },
+    /// Submit a proof file to Aligned and save the claim file
+    Submit {
+        proof: PathBuf,
+        #[arg(short, long)]
+        keystore_path: String,
+    },
+    /// Claim the NFT of a proof verified by Aligned, or retry a failed claim
+    Claim {
+        /// Claim file saved by submit
+        #[arg(long)]
+        from: PathBuf,
+        #[arg(short, long)]
+        keystore_path: String,
+        #[arg(short, long)]
+        leaderboard_verifier_contract_address: H160,
+    },
+    /// Edit a level file and test-play it
+    Editor {
+        #[arg(short, long, default_value = "level.json")]
+        level: PathBuf,
+    },
+    /// Play, prove and submit today's daily challenge
+    Daily {
+        /// Only show today's challenge and the local runs of the day
+        #[arg(long)]
+        list: bool,
+        #[arg(short, long, required_unless_present = "list")]
+        keystore_path: Option<String>,
+        #[arg(short, long, required_unless_present = "list")]
+        leaderboard_verifier_contract_address: Option<H160>,
+    },
+    /// Cycle count of each engine phase of a run inside SP1
+    Profile {
+        /// Replay file, the GameIO JSON, or the hex pub input of a claim
+        game_io: String,
+        /// Also write the report to a file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Local stand-in for the Aligned batcher, to test the submission without live services
+    MockBatcher {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: String,
+        /// Accept the proofs without verifying them, for the mock prover
+        #[arg(long)]
+        mock: bool,
+    },
+    /// Play without wallet or network, the run is validated locally and its replay saved
+    Practice {
+        /// Practice today's daily challenge
+        #[arg(long)]
+        daily: bool,
+    },
+    /// Watch a run: pause, step frame by frame, seek and change the speed
+    Replay {
+        /// Replay file, the GameIO JSON, or the hex pub input of a claim
+        game_io: String,
+    },
+    /// Export a run to an asciinema recording
+    Cast {
+        /// Replay file, the GameIO JSON, or the hex pub input of a claim
+        game_io: String,
+        #[arg(short, long, default_value = "replay.cast")]
+        output: PathBuf,
+    },
+    /// Render a run to SVG and write the ERC-721 metadata of its token
+    Metadata {
+        /// Replay file, the GameIO JSON, or the hex pub input of a claim
+        game_io: String,
+        /// Decimal or 0x prefixed hex, as minted by the verifier contract
+        #[arg(short, long, value_parser = parse_token_id)]
+        token_id: U256,
+        #[arg(short, long, default_value = "metadata")]
+        output: PathBuf,
+        /// Where the SVG will be uploaded, the metadata image points there
+        #[arg(long, default_value = "ipfs://TODO")]
+        base_uri: String,
+        /// Animate the whole run instead of the final board
+        #[arg(long)]
+        animate: bool,
+    },
 }
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
-    let rpc_url = args.rpc_url.clone();
 
+    let ((level, seed, day), keystore_path, contract) = match &args.command {
+        Some(Command::Editor { level }) => {
+            editor::edit(level, args.bindings);
+            return;
+        }
+        Some(Command::Replay { game_io }) => {
+            replay::replay(&replay::load(game_io));
+            return;
+        }
+        Some(Command::Cast { game_io, output }) => {
+            cast::export(&replay::load(game_io), output);
+            return;
+        }
+        Some(Command::Metadata { game_io, token_id, output, base_uri, animate }) => {
+            svg::export(&replay::load(game_io), *token_id, output, base_uri, *animate);
+            return;
+        }
+        Some(Command::Daily { list: true, .. }) => {
+            daily::list(daily::today());
+            return;
+        }
+        Some(Command::Play { daily }) => {
+            let (level, seed, day) = choose_level(*daily, &args.seed);
+            if let Some((_, replay_path)) = play(&level, seed, day, args.ghost.as_deref(), None, args.bindings) {
+                println!("Prove it with: prove {}", replay_path.display());
+            }
+            return;
+        }
+        Some(Command::Prove { replay }) => {
+            if let Some(proof_path) = prove::prove(replay, args.prover, Path::new(prove::PROOFS_FOLDER)) {
+                println!("Submit it with: submit --keystore-path <keystore> {}", proof_path.display());
+            }
+            return;
+        }
+        Some(Command::Submit { proof, keystore_path }) => {
+            let wallet = if args.mock_batcher {
+                decrypt(keystore_path)
+            } else {
+                let (wallet, signer) = connect(keystore_path, &args.rpc_url).await;
+                aligned::deposit(&signer, args.network).await;
+                wallet
+            };
+            let claim_path = aligned::submit_proof(
+                proof,
+                &wallet,
+                &args.rpc_url,
+                &args.batcher_url,
+                args.network,
+                args.mock_batcher,
+            )
+            .await;
+            if let Some(claim_path) = claim_path {
+                println!(
+                    "Claim the NFT with: claim --keystore-path {} --leaderboard-verifier-contract-address <address> --from {}",
+                    keystore_path,
+                    claim_path.display()
+                );
+            }
+            return;
+        }
+        Some(Command::Claim { from, keystore_path, leaderboard_verifier_contract_address }) => {
+            let (_, signer) = connect(keystore_path, &args.rpc_url).await;
+            aligned::claim(from, signer, leaderboard_verifier_contract_address)
+                .await
+                .expect("Claiming of NFT failed ...");
+            return;
+        }
+        Some(Command::Profile { game_io, output }) => {
+            profile::profile(&replay::load(game_io), output.as_deref());
+            return;
+        }
+        Some(Command::MockBatcher { address, mock }) => {
+            mock_batcher::serve(address, *mock).await;
+            return;
+        }
+        Some(Command::Practice { daily }) => {
+            let (level, seed, day) = choose_level(*daily, &args.seed);
+            practice(&level, seed, day, args.ghost.as_deref(), args.bindings);
+            return;
+        }
+        Some(Command::Daily { list: false, keystore_path, leaderboard_verifier_contract_address }) => {
+            (choose_level(true, &args.seed), keystore_path, leaderboard_verifier_contract_address)
+        }
+        None => (
+            choose_level(false, &args.seed),
+            &args.keystore_path,
+            &args.leaderboard_verifier_contract_address,
+        ),
+    };
+
+    // clap requires the keystore to play without --list or a subcommand, and the contract without the mock batcher
+    let Some(keystore_path) = keystore_path else {
+        unreachable!();
+    };
+    let (wallet, claimer) = if args.mock_batcher {
+        (decrypt(keystore_path), None)
+    } else {
+        let (wallet, signer) = connect(keystore_path, &args.rpc_url).await;
+        aligned::deposit(&signer, args.network).await;
+        (wallet, Some((signer, contract.unwrap())))
+    };
+
+    let runs = match &args.replay_file {
+        Some(replay_file) => vec![replay_file.clone()],
+        None => {
+            let player = Some(format!("{:?}", wallet.address()));
+            session::session(|| {
+                play(&level, seed.clone(), day, args.ghost.as_deref(), player.clone(), args.bindings)
+            })
+        }
+    };
+
+    // Each stage leaves its artifact on disk, a failed run can resume from the last one
+    for replay_path in runs {
+        let Some(proof_path) = prove::prove(&replay_path, args.prover, Path::new(prove::PROOFS_FOLDER)) else {
+            continue;
+        };
+        let claim_path = aligned::submit_proof(
+            &proof_path,
+            &wallet,
+            &args.rpc_url,
+            &args.batcher_url,
+            args.network,
+            args.mock_batcher,
+        )
+        .await;
+        let Some(claim_path) = claim_path else {
+            continue;
+        };
+        let Some((signer, leaderboard_verifier_contract_address)) = &claimer else {
+            println!("The mock batch can't be claimed on chain, claim file saved to {}", claim_path.display());
+            continue;
+        };
+
+        println!("Claiming NFT prize...");
+
+        let claim = aligned::claim(&claim_path, signer.clone(), leaderboard_verifier_contract_address).await;
+        if let Err(error) = claim {
+            println!("Claiming of NFT failed: {}", error);
+            println!(
+                "Retry with: claim --keystore-path {} --leaderboard-verifier-contract-address {:?} --from {}",
+                keystore_path,
+                leaderboard_verifier_contract_address,
+                claim_path.display()
+            );
+        }
+    }
+}
+
+// Decrypts the keystore, without touching the network
+fn decrypt(keystore_path: &str) -> LocalWallet {
     let keystore_password = rpassword::prompt_password("Enter keystore password: ")
         .expect("Failed to read keystore password");
 
+    let wallet = LocalWallet::decrypt_keystore(keystore_path, &keystore_password)
+        .expect("Failed to decrypt keystore");
+
+    println!("Wallet Sender: {:?}", wallet.address());
+    wallet
+}
+
+// Decrypts the keystore and connects the wallet to the RPC
+async fn connect(keystore_path: &str, rpc_url: &str) -> (LocalWallet, aligned::Client) {
+    let wallet = decrypt(keystore_path);
+
     let provider =
-        Provider::<Http>::try_from(rpc_url.as_str()).expect("Failed to connect to provider");
+        Provider::<Http>::try_from(rpc_url).expect("Failed to connect to provider");
 
     let chain_id = provider
         .get_chainid()
         .await
         .expect("Failed to get chain_id");
 
-    let wallet = LocalWallet::decrypt_keystore(args.keystore_path, &keystore_password)
-        .expect("Failed to decrypt keystore")
-        .with_chain_id(chain_id.as_u64());
-
-    println!("Wallet Sender: {:?}", wallet.address());
-
-    let signer = SignerMiddleware::new(provider.clone(), wallet.clone());
+    let wallet = wallet.with_chain_id(chain_id.as_u64());
 
-    if Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
-        .with_prompt("Do you want to deposit 0.004eth in Aligned ?\nIf you already deposited Ethereum to Aligned before, this is not needed")
-        .interact()
-        .expect("Failed to read user input") {
-            deposit_to_aligned(U256::from(4000000000000000u128), signer.clone(), args.network).await
-                .expect("Failed to pay for proof submission");
-        }
-
-    // Play Game
-    let game_i_json = game::game_play();
-
-    // Generate proof.
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&game_i_json);
+    let signer = SignerMiddleware::new(provider, wallet.clone());
+    (wallet, signer)
+}
 
-    let game_i: GameIO = serde_json::from_str(&game_i_json).unwrap();
-    let inputs_bytes = hex::decode(&game_i.inputs).unwrap();
+// Token ids are the uint256 of a keccak hash, far beyond a u64
+fn parse_token_id(token_id: &str) -> Result<U256, String> {
+    match token_id.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).map_err(|error| error.to_string()),
+        None => U256::from_dec_str(token_id).map_err(|error| error.to_string()),
+    }
+}
 
-    println!("Generating Proof ");
+// hex_string_to_seed panics on anything but 32 hex encoded bytes
+fn parse_seed(seed: &str) -> Result<String, String> {
+    if seed.len() != 64 || !seed.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("the seed must be 32 bytes encoded as 64 hex characters".to_string());
+    }
+    Ok(seed.to_lowercase())
+}
 
-    let client = ProverClient::new();
-    let (pk, vk) = client.setup(ELF);
+// Today's daily challenge, the level of the seed or the classic one
+fn choose_level(daily: bool, seed: &Option<String>) -> (Level, Option<String>, Option<u32>) {
+    let dimension = game_prove::Vec2::xy(WIDTH, HEIGHT);
+    if daily {
+        let day = daily::today();
+        let seed = generator::daily_seed(day);
+        return (generator::generate(&seed, dimension), Some(seed_to_hex_string(&seed)), Some(day));
+    }
+    match seed {
+        Some(seed) => (generator::generate(&hex_string_to_seed(seed), dimension), Some(seed.clone()), None),
+        None => (Level::classic(dimension), None, None),
+    }
+}
 
-    let Ok(proof) = client.prove(&pk, stdin).run() else {
-        println!("Wrong result!");
+fn practice(
+    level: &Level,
+    seed: Option<String>,
+    day: Option<u32>,
+    ghost: Option<&str>,
+    bindings: input::Bindings,
+) {
+    let Some((game_o, replay_path)) = play(level, seed, day, ghost, None, bindings) else {
         return;
     };
-
-    println!("Proof generated successfully. Verifying proof...");
-
-    client.verify(&proof, &vk).expect("verification failed");
-    println!("Proof verified successfully.");
-
-    println!("Payment successful. Submitting proof...");
-
-    // Serialize proof into bincode (format used by sp1)
-    let proof = bincode::serialize(&proof).expect("Failed to serialize proof");
-
-    let pub_input_struct = PubInput {
-        score: game_i.score,
-        win: game_i.win,
-        end_frame: game_i.end_frame,
-        inputs: inputs_bytes,
-    };
-    let encoded_vec: Vec<u8> = pub_input_struct.encode_to_vec();
-
-    let verification_data = VerificationData {
-        proving_system: ProvingSystemId::SP1,
-        proof,
-        proof_generator_addr: wallet.address(),
-        vm_program_code: Some(ELF.to_vec()),
-        verification_key: None,
-        pub_input: Some(encoded_vec.clone()),
-    };
-
-    let max_fee = estimate_fee(&rpc_url, PriceEstimate::Instant)
-        .await
-        .expect("failed to fetch gas price from the blockchain");
-
-    let max_fee_string = ethers::utils::format_units(max_fee, 18).unwrap();
-
-    if !Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
-        .with_prompt(format!("Aligned will use at most {max_fee_string} eth to verify your proof. Do you want to continue?"))
-        .interact()
-        .expect("Failed to read user input")
-    {   return; }
-
-    let nonce = get_next_nonce(&rpc_url, wallet.address(), args.network)
-        .await
-        .expect("Failed to get next nonce");
-
-        println!("Submitting your proof...");
-
-    let aligned_verification_data = submit_and_wait_verification(
-        &args.batcher_url,
-        &rpc_url,
-        args.network,
-        &verification_data,
-        max_fee,
-        wallet.clone(),
-        nonce,
-    )
-    .await
-    .unwrap();
-
-    println!(
-        "Proof submitted and verified successfully on batch {}",
-        hex::encode(aligned_verification_data.batch_merkle_root)
-    );
-
-    println!("Claiming NFT prize...");
-
-    claim_nft_with_verified_proof(
-        &aligned_verification_data,
-        encoded_vec,
-        signer,
-        &args.leaderboard_verifier_contract_address,
-    )
-    .await
-    .expect("Claiming of NFT failed ...");
+    match game_prove::verify_io(&game_o) {
+        Ok(()) => println!("game_prove agrees with the run, prove it later with: prove {}", replay_path.display()),
+        Err(mismatch) => println!("The prover would reject the run: {}", mismatch),
+    }
 }
 
-async fn claim_nft_with_verified_proof(
-    aligned_verification_data: &AlignedVerificationData,
-    encoded_vec: Vec<u8>,
-    signer: SignerMiddleware<Provider<Http>, LocalWallet>,
-    leaderboard_verifier_contract_addr: &Address,
-) -> anyhow::Result<()> {
-    let leaderboard_verifier_contract = LeaderBoardVerifierContract::new(*leaderboard_verifier_contract_addr, signer.into());
-
-    let index_in_batch = U256::from(aligned_verification_data.index_in_batch);
-    let merkle_path = Bytes::from(
-        aligned_verification_data
-            .batch_inclusion_proof
-            .merkle_path
-            .as_slice()
-            .concat()
-            .to_vec(),
-    );
-
-    let receipt = leaderboard_verifier_contract
-        .verify_batch_inclusion(
-            aligned_verification_data
-                .verification_data_commitment
-                .proof_commitment,
-            aligned_verification_data
-                .verification_data_commitment
-                .pub_input_commitment,
-            aligned_verification_data
-                .verification_data_commitment
-                .proving_system_aux_data_commitment,
-            aligned_verification_data
-                .verification_data_commitment
-                .proof_generator_addr,
-            aligned_verification_data.batch_merkle_root,
-            merkle_path,
-            index_in_batch,
-            encoded_vec.into(),
-        )
-        .send()
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to send tx {}", e))?
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to submit tx {}", e))?;
-
-    match receipt {
-        Some(receipt) => {
-            println!(
-                "Prize claimed successfully. Transaction hash: {:x}",
-                receipt.transaction_hash
-            );
-            Ok(())
+// Plays the level and saves its replay, None if the player leaves it out
+fn play(
+    level: &Level,
+    seed: Option<String>,
+    day: Option<u32>,
+    ghost: Option<&str>,
+    player: Option<String>,
+    bindings: input::Bindings,
+) -> Option<(GameIO, PathBuf)> {
+    let level_key = personal_best::level_key(&seed);
+    let best = personal_best::load(&level_key);
+    let ghost = ghost.map(replay::load).filter(|ghost| {
+        let same_level = game_prove::level_of(ghost) == *level;
+        if !same_level {
+            println!("The ghost run is of another level, playing without it");
         }
-        None => {
-            anyhow::bail!("Failed to claim prize: no receipt");
+        same_level
+    });
+    let (game_o, trace) = game::game_play(level, best.as_ref(), ghost.as_ref(), bindings);
+    let game_o = GameIO { seed, day, ..game_o };
+    let path = replay::save(&game_o, player);
+    println!("Replay saved to {}", path.display());
+
+    if let Some(divergence) = divergence::first_divergence(level, &game_o, &trace) {
+        divergence::report(&divergence);
+        if Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt("The prover will reject this run. Do you want to leave it out of the runs to prove?")
+            .default(true)
+            .interact()
+            .expect("Failed to read user input")
+        {
+            return None;
         }
     }
-}
\ No newline at end of file
+
+    if personal_best::save_if_best(&level_key, &game_o) {
+        println!("New personal best! Time score: {}", game_o.time_score);
+    }
+    Some((game_o, path))
+}
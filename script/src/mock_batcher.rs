@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use aligned_sdk::communication::protocol::EXPECTED_PROTOCOL_VERSION;
+use aligned_sdk::communication::serialization::{cbor_deserialize, cbor_serialize};
+use aligned_sdk::core::types::{
+    BatchInclusionData, ClientMessage, ResponseMessage, VerificationData, VerificationDataCommitment,
+};
+use ethers::utils::keccak256;
+use futures_util::{SinkExt, StreamExt};
+use lambdaworks_crypto::merkle_tree::proof::Proof;
+use sp1_sdk::{ProverClient, SP1ProofWithPublicValues};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message;
+
+// A batch is closed once the connection is quiet for this long
+const BATCH_WINDOW: Duration = Duration::from_millis(500);
+
+// Leaf of the batch Merkle tree, as Aligned hashes a verification data commitment
+fn leaf(commitment: &VerificationDataCommitment) -> [u8; 32] {
+    keccak256(
+        [
+            commitment.proof_commitment.as_slice(),
+            commitment.pub_input_commitment.as_slice(),
+            commitment.proving_system_aux_data_commitment.as_slice(),
+            commitment.proof_generator_addr.as_slice(),
+        ]
+        .concat(),
+    )
+}
+
+// Keccak Merkle tree padded to a power of two with the last leaf, the root and the path of each leaf
+fn merkle_tree(mut level: Vec<[u8; 32]>) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+    let last = *level.last().unwrap();
+    level.resize(level.len().next_power_of_two(), last);
+
+    let mut paths = vec![Vec::new(); level.len()];
+    let mut indexes: Vec<usize> = (0..level.len()).collect();
+    while level.len() > 1 {
+        for (path, index) in paths.iter_mut().zip(indexes.iter_mut()) {
+            path.push(level[*index ^ 1]);
+            *index /= 2;
+        }
+        level = level.chunks(2).map(|pair| keccak256([pair[0], pair[1]].concat())).collect();
+    }
+    (level[0], paths)
+}
+
+// The SP1 proof must verify against the program it claims, unless the batcher runs in mock mode
+fn verify(verification_data: &VerificationData, mock: bool) -> Result<(), String> {
+    if mock {
+        return Ok(());
+    }
+    let elf = verification_data.vm_program_code.as_ref().ok_or("Missing the program")?;
+    let proof: SP1ProofWithPublicValues =
+        bincode::deserialize(&verification_data.proof).map_err(|error| error.to_string())?;
+    let client = ProverClient::new();
+    let (_, vk) = client.setup(elf);
+    client.verify(&proof, &vk).map_err(|error| error.to_string())
+}
+
+async fn send(ws: &mut tokio_tungstenite::WebSocketStream<TcpStream>, response: &ResponseMessage) {
+    let bytes = cbor_serialize(response).expect("Failed to serialize response");
+    if let Err(error) = ws.send(Message::binary(bytes)).await {
+        println!("Failed to send response: {}", error);
+    }
+}
+
+async fn handle(stream: TcpStream, mock: bool) {
+    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    send(&mut ws, &ResponseMessage::ProtocolVersion(EXPECTED_PROTOCOL_VERSION)).await;
+
+    let mut batch = Vec::new();
+    loop {
+        let message = match timeout(BATCH_WINDOW, ws.next()).await {
+            Ok(Some(Ok(Message::Binary(bytes)))) => bytes,
+            Ok(Some(Ok(_))) => continue,
+            Ok(_) => return,
+            Err(_) if batch.is_empty() => continue,
+            // Quiet connection, the batch is closed and every proof gets its inclusion proof
+            Err(_) => {
+                let leaves: Vec<[u8; 32]> = batch.iter().map(leaf).collect();
+                let (root, paths) = merkle_tree(leaves);
+                println!("Batch {} with {} proofs", hex::encode(root), batch.len());
+                for (index_in_batch, merkle_path) in paths.into_iter().take(batch.len()).enumerate() {
+                    let data = BatchInclusionData {
+                        batch_merkle_root: root,
+                        batch_inclusion_proof: Proof { merkle_path },
+                        index_in_batch,
+                    };
+                    send(&mut ws, &ResponseMessage::BatchInclusionData(data)).await;
+                }
+                batch.clear();
+                continue;
+            }
+        };
+
+        let client_message: ClientMessage = match cbor_deserialize(message.as_slice()) {
+            Ok(client_message) => client_message,
+            Err(error) => {
+                send(&mut ws, &ResponseMessage::Error(format!("Invalid message: {:?}", error))).await;
+                continue;
+            }
+        };
+        let verification_data = client_message.verification_data.verification_data;
+        if let Err(error) = verify(&verification_data, mock) {
+            send(&mut ws, &ResponseMessage::Error(format!("Invalid proof: {}", error))).await;
+            continue;
+        }
+        batch.push(VerificationDataCommitment::from(verification_data));
+    }
+}
+
+// Local stand-in for the Aligned batcher, each connection gets its own batches
+pub async fn serve(address: &str, mock: bool) {
+    let listener = TcpListener::bind(address).await.expect("Failed to bind the mock batcher");
+    println!("Mock batcher listening on ws://{}", address);
+    accept(listener, mock).await;
+}
+
+async fn accept(listener: TcpListener, mock: bool) {
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        tokio::spawn(handle(stream, mock));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use aligned_sdk::core::types::Network;
+    use ethers::signers::LocalWallet;
+    use game_prove::replay::Replay;
+    use game_prove::{game_prove, public_values};
+
+    use super::*;
+    use crate::aligned::{self, ClaimFile};
+    use crate::prove::{self, Prover};
+
+    // Folds a leaf up its path, the left or right side given by the index bits
+    fn root_of(leaf: [u8; 32], index: usize, path: &[[u8; 32]]) -> [u8; 32] {
+        path.iter().enumerate().fold(leaf, |hash, (level, sibling)| {
+            if (index >> level) & 1 == 0 {
+                keccak256([hash, *sibling].concat())
+            } else {
+                keccak256([*sibling, hash].concat())
+            }
+        })
+    }
+
+    #[test]
+    fn merkle_paths_lead_to_the_root() {
+        for size in 1..=9usize {
+            let leaves: Vec<[u8; 32]> = (0..size).map(|n| keccak256(n.to_le_bytes())).collect();
+            let (root, paths) = merkle_tree(leaves.clone());
+            assert_eq!(paths.len(), size.next_power_of_two());
+            for (index, leaf) in leaves.iter().enumerate() {
+                assert_eq!(paths[index].len(), size.next_power_of_two().trailing_zeros() as usize);
+                assert_eq!(root_of(*leaf, index, &paths[index]), root, "leaf {} of {}", index, size);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn submit_to_the_mock_batcher() {
+        // Any free port, the listener accepts as soon as it is bound
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(accept(listener, true));
+
+        // A classic run without inputs, proven by the mock prover, with every file in a temp folder
+        let folder = std::env::temp_dir().join(format!("mock_batcher_test_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let game_io = game_prove("");
+        let replay_path = folder.join("run.json");
+        fs::write(&replay_path, serde_json::to_string(&Replay::new(&game_io, None, 0)).unwrap()).unwrap();
+        let proof_path = prove::prove(&replay_path, Some(Prover::Mock), &folder).expect("The mock prover rejected the run");
+
+        // Anvil's first account, the mock batcher doesn't check signatures
+        let wallet: LocalWallet = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let claim_path = aligned::submit_proof(
+            &proof_path,
+            &wallet,
+            "http://127.0.0.1:1",
+            &format!("ws://{}", address),
+            Network::Devnet,
+            true,
+        )
+        .await
+        .expect("The submission was declined");
+
+        let claim_file: ClaimFile = serde_json::from_str(&fs::read_to_string(&claim_path).unwrap()).unwrap();
+        let data = &claim_file.aligned_verification_data;
+        assert_eq!(claim_file.pub_input, hex::encode(public_values::encode(&game_io)));
+        assert_eq!(data.index_in_batch, 0);
+        assert_eq!(
+            root_of(leaf(&data.verification_data_commitment), 0, &data.batch_inclusion_proof.merkle_path),
+            data.batch_merkle_root
+        );
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
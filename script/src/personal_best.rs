@@ -0,0 +1,37 @@
+use std::fs;
+use std::path::PathBuf;
+
+use game_prove::{game_prove_io, GameIO};
+
+const PERSONAL_BEST_FOLDER: &str = "replays/personal_best";
+
+// One personal best per level: the seed, or "classic"
+pub fn level_key(seed: &Option<String>) -> String {
+    seed.clone().unwrap_or_else(|| "classic".to_string())
+}
+
+fn path(level_key: &str) -> PathBuf {
+    PathBuf::from(PERSONAL_BEST_FOLDER).join(format!("{}.json", level_key))
+}
+
+pub fn load(level_key: &str) -> Option<GameIO> {
+    let json = fs::read_to_string(path(level_key)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+// Keeps the run if it has the best time score of the level and the prover agrees with it
+pub fn save_if_best(level_key: &str, game_io: &GameIO) -> bool {
+    let best_time_score = load(level_key).map_or(0, |best| best.time_score);
+    if game_io.time_score <= best_time_score {
+        return false;
+    }
+    let game_o = game_prove_io(game_io);
+    if game_o.time_score != game_io.time_score || game_o.inputs != game_io.inputs {
+        return false;
+    }
+
+    fs::create_dir_all(PERSONAL_BEST_FOLDER).expect("Failed to create personal best folder");
+    fs::write(path(level_key), serde_json::to_string(game_io).unwrap())
+        .expect("Failed to write personal best");
+    true
+}
@@ -0,0 +1,39 @@
+use std::fs;
+use std::path::Path;
+
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+use game_prove::GameIO;
+
+// Built with `make build-profile-elf`, the program with the cycle tracker markers of game_prove
+const PROFILE_ELF: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../program/elf/riscv32im-succinct-zkvm-elf-profile"
+);
+
+// Executes the run on the profile ELF, one line per engine phase sorted by name
+// so reports of different commits can be diffed
+pub fn profile(game_io: &GameIO, output: Option<&Path>) {
+    let elf = fs::read(PROFILE_ELF)
+        .expect("Failed to read the profile ELF, build it with `make build-profile-elf`");
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&serde_json::to_string(game_io).unwrap());
+
+    let client = ProverClient::new();
+    let (_, report) = client.execute(&elf, stdin).run().expect("Execution failed");
+
+    let mut phases: Vec<(&String, &u64)> = report.cycle_tracker.iter().collect();
+    phases.sort();
+    let mut text = String::new();
+    for (phase, cycles) in phases {
+        text.push_str(&format!("{:<16}{:>12}\n", phase, cycles));
+    }
+    text.push_str(&format!("{:<16}{:>12}\n", "total", report.total_instruction_count()));
+
+    print!("{}", text);
+    if let Some(output) = output {
+        fs::write(output, text).expect("Failed to write the profile report");
+        println!("Report saved to {}", output.display());
+    }
+}
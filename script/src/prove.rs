@@ -0,0 +1,139 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+use game_prove::{public_values, verify_io, GameIO};
+
+use crate::replay;
+
+pub const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+pub const PROOFS_FOLDER: &str = "proofs";
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Prover {
+    Local,
+    // Structurally valid proofs without proving, for fast end-to-end tests
+    Mock,
+}
+
+// Without a prover option, SP1 picks it from the SP1_PROVER environment variable
+fn client(prover: Option<Prover>) -> ProverClient {
+    match prover {
+        None => ProverClient::new(),
+        Some(Prover::Local) => ProverClient::local(),
+        Some(Prover::Mock) => ProverClient::mock(),
+    }
+}
+
+// Output of the prove stage, the run travels with its proof so submitting needs nothing else
+#[derive(Serialize, Deserialize)]
+pub struct ProofFile {
+    pub game_io: GameIO,
+    // SP1 proof serialized into bincode, the format Aligned expects
+    pub proof: Vec<u8>,
+    // Public values committed by the program, the pub input of Aligned and the contract
+    pub pub_input: Vec<u8>,
+}
+
+// Artifacts of a run are named after its replay file, on the given folder
+fn artifact_path(run: &Path, folder: &Path, extension: &str) -> PathBuf {
+    let name = run.file_stem().expect("Invalid file name");
+    folder.join(name).with_extension(extension)
+}
+
+pub fn load(path: &Path) -> ProofFile {
+    let bytes = fs::read(path).expect("Failed to read proof file");
+    bincode::deserialize(&bytes).expect("Failed to parse proof file")
+}
+
+// Checks the run natively and then runs the ELF through the SP1 executor with the same stdin,
+// an invalid run fails here before paying the proving cost
+fn preflight(client: &ProverClient, stdin: &SP1Stdin, game_io: &GameIO) -> bool {
+    // game_prove gives the same mismatch the guest prints to stderr, without the executor
+    if let Err(mismatch) = verify_io(game_io) {
+        println!("Preflight failed: {}", mismatch);
+        return false;
+    }
+
+    // The run is valid, so a guest that rejects it or commits other values was built from other sources
+    println!("Executing preflight...");
+    match client.execute(ELF, stdin.clone()).run() {
+        Ok((committed, report)) if committed.to_vec() == public_values::encode(game_io) => {
+            println!("Preflight succeeded in {} cycles", report.total_instruction_count());
+            true
+        }
+        Ok(_) => {
+            println!("Preflight failed: the ELF commits other public values than game_prove, rebuild it with make build-elf");
+            false
+        }
+        Err(error) => {
+            println!("Preflight failed: game_prove accepts the run but the ELF doesn't, rebuild it with make build-elf");
+            println!("Executor error: {}", error);
+            false
+        }
+    }
+}
+
+// Proves the run of a replay file and writes the proof file to the folder, None if the prover rejects the run
+pub fn prove(replay_path: &Path, prover: Option<Prover>, folder: &Path) -> Option<PathBuf> {
+    let game_io = replay::read(replay_path);
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&serde_json::to_string(&game_io).unwrap());
+
+    let client = client(prover);
+    if !preflight(&client, &stdin, &game_io) {
+        return None;
+    }
+
+    println!("Generating Proof ");
+
+    let (pk, vk) = client.setup(ELF);
+
+    let Ok(proof) = client.prove(&pk, stdin).run() else {
+        println!("Wrong result!");
+        return None;
+    };
+
+    println!("Proof generated successfully. Verifying proof...");
+
+    client.verify(&proof, &vk).expect("verification failed");
+    println!("Proof verified successfully.");
+
+    let proof_file = ProofFile {
+        game_io,
+        proof: bincode::serialize(&proof).expect("Failed to serialize proof"),
+        pub_input: proof.public_values.to_vec(),
+    };
+    fs::create_dir_all(folder).expect("Failed to create proofs folder");
+    let path = artifact_path(replay_path, folder, "proof");
+    fs::write(&path, bincode::serialize(&proof_file).unwrap()).expect("Failed to write proof file");
+    println!("Proof saved to {}", path.display());
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_prove::game_prove_daily;
+
+    #[test]
+    fn proof_file_round_trip() {
+        let proof_file = ProofFile {
+            game_io: game_prove_daily(20000, "0440"),
+            proof: vec![1, 2, 3],
+            pub_input: vec![4, 5],
+        };
+        let path = std::env::temp_dir().join("proof_file_round_trip.proof");
+        fs::write(&path, bincode::serialize(&proof_file).unwrap()).unwrap();
+
+        let loaded = load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(public_values::encode(&loaded.game_io), public_values::encode(&proof_file.game_io));
+        assert_eq!(loaded.proof, proof_file.proof);
+        assert_eq!(loaded.pub_input, proof_file.pub_input);
+    }
+}
@@ -0,0 +1,170 @@
+use ruscii::drawing::Pencil;
+use ruscii::spatial::Vec2;
+use ruscii::terminal::{Color, Style, Window};
+
+use game_prove::{AlienKind, GameState, HEIGHT, WIDTH};
+
+// Colours independent of the drawing backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tint {
+    Cyan,
+    White,
+    Red,
+    Green,
+    Blue,
+    Magenta,
+    Yellow,
+    Grey,
+}
+
+pub struct Glyph {
+    pub position: game_prove::Vec2,
+    pub symbol: char,
+    pub tint: Tint,
+    pub bold: bool,
+}
+
+// Read-only view of the engine, everything a renderer can draw
+pub struct View<'a> {
+    pub state: &'a GameState,
+    pub frame: usize,
+    pub status: String,
+    // Ship of the ghost run, drawn dimmed under everything else
+    pub ghost: Option<game_prove::Vec2>,
+}
+
+impl<'a> View<'a> {
+    pub fn new(state: &'a GameState, frame: usize) -> View<'a> {
+        View {
+            state,
+            frame,
+            status: format!("lives: {}  -  score: {}", state.lives, state.score),
+            ghost: None,
+        }
+    }
+
+    // The board in drawing order, later glyphs cover earlier ones
+    pub fn glyphs(&self) -> Vec<Glyph> {
+        let glyph = |position, symbol, tint| Glyph { position, symbol, tint, bold: false };
+        let state = self.state;
+        let ship = state.spaceship;
+
+        let mut glyphs = vec![];
+        if let Some(ghost) = self.ghost {
+            glyphs.extend([
+                glyph(ghost, '^', Tint::Grey),
+                glyph(game_prove::Vec2::xy(ghost.x - 1, ghost.y), '/', Tint::Grey),
+                glyph(game_prove::Vec2::xy(ghost.x + 1, ghost.y), '\\', Tint::Grey),
+            ]);
+        }
+        glyphs.extend([
+            glyph(ship, '^', Tint::Cyan),
+            glyph(game_prove::Vec2::xy(ship.x - 1, ship.y), '/', Tint::Cyan),
+            glyph(game_prove::Vec2::xy(ship.x + 1, ship.y), '\\', Tint::Cyan),
+            glyph(game_prove::Vec2::xy(ship.x, ship.y + 1), '\'', Tint::Cyan),
+        ]);
+        glyphs.extend(state.bunkers.iter().map(|bunker| glyph(*bunker, '#', Tint::White)));
+        glyphs.extend(state.aliens_shots.iter().map(|shot| glyph(*shot, '|', Tint::Red)));
+        glyphs.extend(state.aliens.iter().map(|alien| {
+            let (symbol, tint) = alien_glyph(alien.kind);
+            glyph(alien.position, symbol, tint)
+        }));
+        glyphs.extend(state.spaceship_shots.iter().map(|shot| Glyph {
+            bold: true,
+            ..glyph(*shot, '|', Tint::Yellow)
+        }));
+        glyphs
+    }
+}
+
+pub fn alien_glyph(kind: AlienKind) -> (char, Tint) {
+    match kind {
+        AlienKind::Grunt => ('W', Tint::Green),
+        AlienKind::Soldier => ('M', Tint::Blue),
+        AlienKind::Elite => ('X', Tint::Magenta),
+    }
+}
+
+pub trait Renderer {
+    fn draw(&mut self, view: &View);
+}
+
+// Draws on the ruscii window, centering the board
+pub struct RusciiRenderer<'a> {
+    pencil: Pencil<'a>,
+    board_origin: Vec2,
+}
+
+impl<'a> RusciiRenderer<'a> {
+    pub fn new(window: &'a mut Window) -> RusciiRenderer<'a> {
+        let board_origin = (window.size() - Vec2::xy(WIDTH, HEIGHT)) / 2;
+        RusciiRenderer {
+            pencil: Pencil::new(window.canvas_mut()),
+            board_origin,
+        }
+    }
+
+    // For the HUD around the board, draw leaves it at the board origin
+    pub fn pencil(&mut self) -> &mut Pencil<'a> {
+        &mut self.pencil
+    }
+}
+
+impl Renderer for RusciiRenderer<'_> {
+    fn draw(&mut self, view: &View) {
+        let pencil = &mut self.pencil;
+        pencil.set_origin(self.board_origin);
+        pencil.draw_text(&view.status, Vec2::xy(15, 0));
+        for glyph in view.glyphs() {
+            pencil.set_foreground(color(glyph.tint));
+            pencil.set_style(if glyph.bold { Style::Bold } else { Style::Plain });
+            pencil.draw_char(glyph.symbol, screen(glyph.position));
+        }
+        pencil.set_foreground(Color::White);
+        pencil.set_style(Style::Plain);
+    }
+}
+
+// Converts an engine position into a ruscii one
+pub fn screen(position: game_prove::Vec2) -> Vec2 {
+    Vec2::xy(position.x, position.y)
+}
+
+pub fn color(tint: Tint) -> Color {
+    match tint {
+        Tint::Cyan => Color::Cyan,
+        Tint::White => Color::White,
+        Tint::Red => Color::Red,
+        Tint::Green => Color::Green,
+        Tint::Blue => Color::Blue,
+        Tint::Magenta => Color::Magenta,
+        Tint::Yellow => Color::Yellow,
+        Tint::Grey => Color::DarkGrey,
+    }
+}
+
+// Headless renderer, keeps every drawn frame as plain text
+#[derive(Default)]
+pub struct TextRenderer {
+    pub frames: Vec<String>,
+}
+
+impl Renderer for TextRenderer {
+    fn draw(&mut self, view: &View) {
+        let mut rows = vec![vec![' '; WIDTH as usize + 2]; HEIGHT as usize + 1];
+        for glyph in view.glyphs() {
+            let row = usize::try_from(glyph.position.y).ok().and_then(|y| rows.get_mut(y));
+            let cell = row.and_then(|row| row.get_mut(usize::try_from(glyph.position.x).ok()?));
+            if let Some(cell) = cell {
+                *cell = glyph.symbol;
+            }
+        }
+
+        let mut text = format!("frame: {}  -  {}\n", view.frame, view.status);
+        for row in rows {
+            text.extend(row);
+            text.push('\n');
+        }
+        self.frames.push(text);
+    }
+}
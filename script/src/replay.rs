@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ruscii::app::{App, Config, State};
+use ruscii::keyboard::{Key, KeyEvent};
+use ruscii::spatial::Vec2;
+use ruscii::terminal::Window;
+
+use game_prove::public_values;
+use game_prove::replay::{Replay, ENGINE_VERSION};
+use game_prove::snapshot::Snapshot;
+use game_prove::{game_prove_io, level_of, GameIO, JsonGameIO, Simulation, MAX_FRAME};
+
+use crate::daily;
+use crate::render::{Renderer, RusciiRenderer, View};
+use crate::ticker::Ticker;
+
+// Playback speeds in quarters of the live speed
+const SPEEDS: [usize; 5] = [1, 2, 4, 8, 16];
+const NORMAL_SPEED: usize = 2;
+const SEEK_FRAMES: usize = 100;
+const RUNS_FOLDER: &str = "replays/runs";
+
+// Writes the replay file of a run, daily runs go to the folder of their day
+pub fn save(game_io: &GameIO, player: Option<String>) -> PathBuf {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let replay = Replay::new(game_io, player, now.as_secs());
+    let folder = match game_io.day {
+        Some(day) => daily::day_folder(day),
+        None => PathBuf::from(RUNS_FOLDER),
+    };
+    fs::create_dir_all(&folder).expect("Failed to create replays folder");
+
+    let path = folder.join(format!("{}.json", now.as_millis()));
+    fs::write(&path, serde_json::to_string_pretty(&replay).unwrap())
+        .expect("Failed to write replay");
+    path
+}
+
+// A replay file, or the bare GameIO JSON older versions printed
+pub fn parse(json: &str) -> Result<GameIO, String> {
+    let Ok(replay) = serde_json::from_str::<Replay>(json) else {
+        return serde_json::from_str::<JsonGameIO>(json)
+            .map(GameIO::from)
+            .map_err(|error| error.to_string());
+    };
+    replay.check()?;
+    if replay.engine_version != ENGINE_VERSION {
+        println!(
+            "Warning: the replay was recorded with engine {}, this is {}",
+            replay.engine_version, ENGINE_VERSION
+        );
+    }
+    Ok(replay.game_io())
+}
+
+pub fn read(path: &Path) -> GameIO {
+    let json = fs::read_to_string(path).expect("Failed to read replay file");
+    parse(&json).expect("Failed to parse replay file")
+}
+
+// Accepts a replay file, the JSON itself, or the hex pub input of a claim(from its calldata or claim file)
+pub fn load(game_io: &str) -> GameIO {
+    let text = fs::read_to_string(game_io).unwrap_or_else(|_| game_io.to_string());
+    let text = text.trim();
+    if let Ok(pub_input) = hex::decode(text.trim_start_matches("0x")) {
+        return public_values::decode(&pub_input).expect("Failed to decode pub input");
+    }
+    parse(text).expect("Failed to parse replay")
+}
+
+// Re-simulates the run with game_prove, every state is kept so seeking is instant
+pub fn trace(game_io: &GameIO) -> Vec<Snapshot> {
+    let mut simulation = Simulation::new(&level_of(game_io), &game_io.inputs);
+    let mut trace = vec![simulation.snapshot()];
+    while !simulation.is_over() {
+        simulation.step();
+        trace.push(simulation.snapshot());
+    }
+    trace
+}
+
+pub fn replay(game_io: &GameIO) {
+    let trace = trace(game_io);
+    let game_o = game_prove_io(game_io);
+    println!(
+        "Re-simulated: score {}, win {}, end frame {}",
+        game_o.score, game_o.win, game_o.end_frame
+    );
+    let result = |game_io: &GameIO| (game_io.score, game_io.win, game_io.end_frame);
+    if result(&game_o) != result(game_io) {
+        println!(
+            "The claimed result differs: score {}, win {}, end frame {}",
+            game_io.score, game_io.win, game_io.end_frame
+        );
+    }
+
+    let mut app = App::config(Config::new().fps(60));
+    let mut ticker = Ticker::start();
+    let mut position = 0;
+    let mut paused = false;
+    let mut speed = NORMAL_SPEED;
+    // Elapsed ticks in quarters, the fraction carries over to the next rendered frame
+    let mut quarters = 0;
+
+    app.run(|app_state: &mut State, window: &mut Window| {
+        for key_event in app_state.keyboard().last_key_events() {
+            match key_event {
+                KeyEvent::Pressed(Key::Esc) | KeyEvent::Pressed(Key::Q) => {
+                    app_state.stop();
+                    return;
+                }
+                KeyEvent::Pressed(Key::Space) => paused = !paused,
+                KeyEvent::Pressed(Key::Up) => speed = (speed + 1).min(SPEEDS.len() - 1),
+                KeyEvent::Pressed(Key::Down) => speed = speed.saturating_sub(1),
+                // Stepping pauses, so the frame stays on screen
+                KeyEvent::Pressed(Key::Right) => {
+                    paused = true;
+                    position += 1;
+                }
+                KeyEvent::Pressed(Key::Left) => {
+                    paused = true;
+                    position = position.saturating_sub(1);
+                }
+                KeyEvent::Pressed(Key::F) => position += SEEK_FRAMES,
+                KeyEvent::Pressed(Key::B) => position = position.saturating_sub(SEEK_FRAMES),
+                KeyEvent::Pressed(Key::R) => position = 0,
+                _ => (),
+            }
+        }
+
+        // Ticks keep being counted while paused, so resuming doesn't jump ahead
+        let due = ticker.due();
+        if !paused {
+            quarters += due * SPEEDS[speed];
+            position += quarters / 4;
+            quarters %= 4;
+        }
+        position = position.min(trace.len() - 1);
+
+        let snapshot = &trace[position];
+        let mut renderer = RusciiRenderer::new(window);
+        let pencil = renderer.pencil();
+        pencil.draw_text(
+            &format!(
+                "frame: {}/{}  -  speed: {}x{}",
+                snapshot.frame,
+                MAX_FRAME,
+                SPEEDS[speed] as f32 / 4.0,
+                if paused { "  -  paused" } else { "" }
+            ),
+            Vec2::xy(1, 0),
+        );
+        pencil.draw_text(
+            "space: pause  left/right: step  up/down: speed  b/f: seek  r: restart  q: quit",
+            Vec2::xy(1, 1),
+        );
+        renderer.draw(&View::new(&snapshot.state, snapshot.frame));
+    });
+}
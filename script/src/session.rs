@@ -0,0 +1,59 @@
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, MultiSelect};
+use std::path::PathBuf;
+
+use game_prove::GameIO;
+
+fn describe(game_io: &GameIO) -> String {
+    format!(
+        "score {}, {}, end frame {}, time score {}",
+        game_io.score,
+        if game_io.win { "win" } else { "loss" },
+        game_io.end_frame,
+        game_io.time_score
+    )
+}
+
+// Plays runs until the player stops, then returns the replay files of the ones chosen to prove.
+// play returns None for a run left out of the session
+pub fn session(mut play: impl FnMut() -> Option<(GameIO, PathBuf)>) -> Vec<PathBuf> {
+    let mut runs = Vec::new();
+    loop {
+        if let Some(run) = play() {
+            runs.push(run);
+        }
+
+        println!("Runs of this session:");
+        for (n, (run, _)) in runs.iter().enumerate() {
+            println!("  {}. {}", n + 1, describe(run));
+        }
+        if !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Do you want to play another run?")
+            .default(true)
+            .interact()
+            .expect("Failed to read user input")
+        {
+            break;
+        }
+    }
+    if runs.is_empty() {
+        return Vec::new();
+    }
+
+    // The best score is preselected, proving every run is rarely worth it
+    let best = (0..runs.len()).max_by_key(|n| (runs[*n].0.score, runs[*n].0.time_score));
+    let defaults: Vec<bool> = (0..runs.len()).map(|n| Some(n) == best).collect();
+    let items: Vec<String> = runs.iter().map(|(run, _)| describe(run)).collect();
+    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Choose the runs to prove and submit(space to toggle, enter to confirm)")
+        .items(&items)
+        .defaults(&defaults)
+        .interact()
+        .expect("Failed to read user input");
+
+    runs.into_iter()
+        .enumerate()
+        .filter(|(n, _)| selected.contains(n))
+        .map(|(_, (_, path))| path)
+        .collect()
+}
@@ -0,0 +1,47 @@
+use game_prove::level::Level;
+use game_prove::{GameEvent, GameIO, Simulation};
+
+// A split every 25% of the aliens defeated
+pub const SPLITS: usize = 4;
+
+pub struct Splits {
+    total_aliens: usize,
+    destroyed: usize,
+    pub frames: Vec<u16>,
+}
+
+impl Splits {
+    pub fn new(total_aliens: usize) -> Splits {
+        Splits {
+            total_aliens,
+            destroyed: 0,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, frame: usize, events: &[GameEvent]) {
+        for event in events {
+            if !matches!(event, GameEvent::AlienDestroyed(_)) {
+                continue;
+            }
+            self.destroyed += 1;
+            while self.frames.len() < SPLITS
+                && self.destroyed * SPLITS >= self.total_aliens * (self.frames.len() + 1)
+            {
+                self.frames.push(frame as u16);
+            }
+        }
+    }
+}
+
+// Splits of a stored run, from the events of its re-simulation
+pub fn from_replay(level: &Level, game_io: &GameIO) -> Splits {
+    let mut simulation = Simulation::new(level, &game_io.inputs);
+    let mut splits = Splits::new(level.aliens.len());
+    while !simulation.is_over() {
+        let frame = simulation.frame;
+        let events = simulation.step();
+        splits.record(frame, &events);
+    }
+    splits
+}
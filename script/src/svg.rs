@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::Path;
+
+use ethers::types::U256;
+use serde_json::{json, Value};
+
+use game_prove::{game_prove_io, GameIO, HEIGHT, WIDTH};
+
+use crate::render::{Renderer, Tint, View};
+use crate::replay;
+use crate::ticker::TICKS_PER_SECOND;
+
+// Size of a terminal cell in pixels
+const CELL_WIDTH: i32 = 10;
+const CELL_HEIGHT: i32 = 18;
+
+fn hex(tint: Tint) -> &'static str {
+    match tint {
+        Tint::Cyan => "#00d7d7",
+        Tint::White => "#e4e4e4",
+        Tint::Red => "#ff5f5f",
+        Tint::Green => "#5fd75f",
+        Tint::Blue => "#5f87ff",
+        Tint::Magenta => "#d75fd7",
+        Tint::Yellow => "#ffd75f",
+        Tint::Grey => "#6c6c6c",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn text(x: i32, y: i32, fill: &str, bold: bool, content: &str) -> String {
+    format!(
+        "<text x=\"{}\" y=\"{}\" fill=\"{}\"{}>{}</text>",
+        x * CELL_WIDTH,
+        (y + 1) * CELL_HEIGHT - 4,
+        fill,
+        if bold { " font-weight=\"bold\"" } else { "" },
+        escape(content)
+    )
+}
+
+// Renders each view as an SVG group of text cells, same glyphs as the terminal
+#[derive(Default)]
+pub struct SvgRenderer {
+    pub frames: Vec<(usize, String)>,
+}
+
+impl Renderer for SvgRenderer {
+    fn draw(&mut self, view: &View) {
+        let mut group = text(15, 0, hex(Tint::White), false, &view.status);
+        for glyph in view.glyphs() {
+            let (x, y) = (glyph.position.x, glyph.position.y);
+            let symbol = glyph.symbol.to_string();
+            group.push_str(&text(x, y, hex(glyph.tint), glyph.bold, &symbol));
+        }
+
+        // Frames without visible changes are left out, the previous one stays on screen
+        if self.frames.last().map(|(_, last)| last) == Some(&group) {
+            return;
+        }
+        self.frames.push((view.frame, group));
+    }
+}
+
+fn document(body: &str) -> String {
+    let width = (WIDTH + 2) * CELL_WIDTH;
+    let height = (HEIGHT + 1) * CELL_HEIGHT;
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+viewBox=\"0 0 {width} {height}\" font-family=\"monospace\" font-size=\"16\">\
+<rect width=\"100%\" height=\"100%\" fill=\"#000000\"/>{body}</svg>"
+    )
+}
+
+// The board after the last frame of the game_prove re-simulation
+pub fn final_board(game_io: &GameIO) -> String {
+    let last = replay::trace(game_io).pop().unwrap();
+    let mut renderer = SvgRenderer::default();
+    renderer.draw(&View::new(&last.state, last.frame));
+    document(&renderer.frames[0].1)
+}
+
+// Every frame of the run as a group shown only during its time, looping at the live speed
+pub fn animated(game_io: &GameIO) -> String {
+    let trace = replay::trace(game_io);
+    let mut renderer = SvgRenderer::default();
+    for snapshot in &trace {
+        renderer.draw(&View::new(&snapshot.state, snapshot.frame));
+    }
+
+    // The last frame is held for a second before looping
+    let total = trace.len() + TICKS_PER_SECOND as usize;
+    let duration = total as f64 / TICKS_PER_SECOND as f64;
+    let mut body = String::new();
+    for (n, (frame, group)) in renderer.frames.iter().enumerate() {
+        let start = *frame as f64 / total as f64;
+        let end = renderer.frames.get(n + 1).map_or(1.0, |(next, _)| *next as f64 / total as f64);
+
+        // Discrete keyTimes must start at 0, and only end at 1 for the last frame
+        let mut values = vec![];
+        let mut key_times = vec![];
+        if start > 0.0 {
+            values.push("none");
+            key_times.push(0.0);
+        }
+        values.push("inline");
+        key_times.push(start);
+        if end < 1.0 {
+            values.push("none");
+            key_times.push(end);
+        }
+        body.push_str(&format!(
+            "<g display=\"none\"><animate attributeName=\"display\" values=\"{}\" keyTimes=\"{}\" \
+calcMode=\"discrete\" dur=\"{:.3}s\" repeatCount=\"indefinite\"/>{}</g>",
+            values.join(";"),
+            key_times.iter().map(|time| format!("{:.5}", time)).collect::<Vec<_>>().join(";"),
+            duration,
+            group
+        ));
+    }
+    document(&body)
+}
+
+// ERC-721 metadata, the attributes come from the re-simulation and not from the claimed result
+pub fn metadata(game_io: &GameIO, token_id: U256, image: &str) -> Value {
+    let game_o = game_prove_io(game_io);
+    json!({
+        "name": format!("Space Aligners #{}", token_id),
+        "description": "A Space Aligners run, proven with SP1 and verified on Aligned",
+        "image": image,
+        "attributes": [
+            { "trait_type": "Score", "value": game_o.score },
+            { "trait_type": "Win", "value": game_o.win },
+            { "trait_type": "End frame", "value": game_o.end_frame },
+            { "trait_type": "Outcome", "value": if game_o.win { "Victory" } else { "Defeat" } },
+        ],
+    })
+}
+
+// Writes <token_id>.svg and <token_id>.json, the image is expected under base_uri
+pub fn export(game_io: &GameIO, token_id: U256, folder: &Path, base_uri: &str, animate: bool) {
+    fs::create_dir_all(folder).expect("Failed to create metadata folder");
+
+    let svg = if animate { animated(game_io) } else { final_board(game_io) };
+    let svg_path = folder.join(format!("{}.svg", token_id));
+    fs::write(&svg_path, svg).expect("Failed to write SVG");
+
+    let image = format!("{}/{}.svg", base_uri.trim_end_matches('/'), token_id);
+    let metadata = metadata(game_io, token_id, &image);
+    let json_path = folder.join(format!("{}.json", token_id));
+    fs::write(&json_path, serde_json::to_string_pretty(&metadata).unwrap())
+        .expect("Failed to write metadata");
+
+    println!("Metadata saved to {} and {}", svg_path.display(), json_path.display());
+}
@@ -0,0 +1,29 @@
+use std::time::Instant;
+
+// Simulation frames per second, 1023 frames are always ~34 seconds
+pub const TICKS_PER_SECOND: u128 = 30;
+// Ticks run at most per rendered frame, after a terminal stall the rest catch up in the next ones
+const MAX_CATCH_UP: usize = 4;
+
+// Fixed timestep scheduler, decoupled from the terminal render rate
+pub struct Ticker {
+    start: Instant,
+    ticks: usize,
+}
+
+impl Ticker {
+    pub fn start() -> Ticker {
+        Ticker {
+            start: Instant::now(),
+            ticks: 0,
+        }
+    }
+
+    // Simulation ticks due since the last call
+    pub fn due(&mut self) -> usize {
+        let target = (self.start.elapsed().as_micros() * TICKS_PER_SECOND / 1_000_000) as usize;
+        let due = target.saturating_sub(self.ticks).min(MAX_CATCH_UP);
+        self.ticks += due;
+        due
+    }
+}